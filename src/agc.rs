@@ -0,0 +1,136 @@
+use std::time::Instant;
+
+// automatic gain control, follows the peak of the incoming data and scales it towards `target`
+pub struct Agc {
+    pub enabled: bool,
+    pub attack: f32,  // in seconds
+    pub release: f32, // in seconds
+    pub target: f32,
+    envelope: f32,
+    gain: f32,
+    last_update: Instant,
+}
+
+impl Agc {
+    pub fn process(&mut self, data: &mut [f32]) {
+        self.process_at(data, Instant::now())
+    }
+
+    // `process` for a spectrum taken at `now`
+    pub fn process_at(&mut self, data: &mut [f32], now: Instant) {
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        if !self.enabled {
+            self.gain = 1.0;
+            return;
+        }
+
+        let peak = data.iter().cloned().fold(0.0_f32, f32::max);
+
+        // rises with the attack time constant and falls with the release time constant,
+        // based on elapsed time so it behaves the same at every refresh rate
+        let tau = if peak > self.envelope { self.attack } else { self.release };
+        let coefficient = 1.0 - (-dt / tau.max(0.001)).exp();
+        self.envelope += (peak - self.envelope) * coefficient;
+
+        self.gain = (self.target / self.envelope.max(0.0001)).clamp(0.01, 100.0);
+
+        for value in data.iter_mut() {
+            *value *= self.gain;
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+impl Default for Agc {
+    fn default() -> Self {
+        Agc {
+            enabled: false,
+            attack: 0.05,
+            release: 2.0,
+            target: 0.8,
+            envelope: 0.0,
+            gain: 1.0,
+            last_update: Instant::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const RATE: f32 = 60.0;
+
+    // feeds `seconds` of spectra with `peak` as their loudest bar, returns the last output peak
+    fn run(agc: &mut Agc, start: &mut Instant, peak: f32, seconds: f32) -> f32 {
+        let mut output = 0.0;
+        for _ in 0..(seconds * RATE) as usize {
+            *start += Duration::from_secs_f32(1.0 / RATE);
+            let mut data = vec![peak * 0.5, peak, peak * 0.25];
+            agc.process_at(&mut data, *start);
+            output = data.iter().cloned().fold(0.0, f32::max);
+        }
+        output
+    }
+
+    fn enabled() -> (Agc, Instant) {
+        let agc = Agc { enabled: true, ..Default::default() };
+        let start = agc.last_update;
+        (agc, start)
+    }
+
+    #[test]
+    fn converges_to_the_target() {
+        for peak in [0.05, 0.5, 4.0].iter() {
+            let (mut agc, mut start) = enabled();
+            let output = run(&mut agc, &mut start, *peak, 20.0);
+            assert!((output - agc.target).abs() < 0.01, "{} ends at {}", peak, output);
+        }
+    }
+
+    #[test]
+    fn attack_is_faster_than_release() {
+        let (mut agc, mut start) = enabled();
+        let (attack, release, target) = (agc.attack, agc.release, agc.target);
+        run(&mut agc, &mut start, 0.2, 20.0);
+
+        // louder input is turned down within a few attack times
+        let output = run(&mut agc, &mut start, 2.0, 5.0 * attack);
+        assert!((output - target).abs() < 0.1, "attack ends at {}", output);
+
+        // quieter input needs about the release time to come back up
+        let output = run(&mut agc, &mut start, 0.2, 5.0 * attack);
+        assert!(output < 0.5 * target, "release already at {}", output);
+        let output = run(&mut agc, &mut start, 0.2, 5.0 * release);
+        assert!((output - target).abs() < 0.1, "release ends at {}", output);
+    }
+
+    #[test]
+    fn silence() {
+        let (mut agc, mut start) = enabled();
+        run(&mut agc, &mut start, 0.5, 5.0);
+        assert_eq!(run(&mut agc, &mut start, 0.0, 60.0), 0.0);
+        assert!(agc.gain().is_finite() && agc.gain() <= 100.0, "gain is {}", agc.gain());
+
+        // no data at all and a second call at the same instant
+        let mut empty: [f32; 0] = [];
+        agc.process_at(&mut empty, start);
+        agc.process_at(&mut empty, start);
+        assert!(agc.gain().is_finite() && agc.gain() <= 100.0, "gain is {}", agc.gain());
+    }
+
+    #[test]
+    fn disabled_leaves_the_data() {
+        let mut agc = Agc::default();
+        let mut data = vec![0.1, 3.0];
+        agc.process_at(&mut data, agc.last_update + Duration::from_millis(16));
+        assert_eq!(data, vec![0.1, 3.0]);
+        assert_eq!(agc.gain(), 1.0);
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::audio::{self, AudioDevice, Samples, Taps};
+use crate::spectrum::{self, SpectrumFrame, SpectrumReader};
+
+// owns the capture stream, audioviz and the spectrum fetcher
+pub struct Analyzer {
+    event_sender: mpsc::Sender<audioviz::Event>,
+    device_sender: mpsc::Sender<AudioDevice>,
+    refresh_rate: Arc<AtomicUsize>,
+    max_frequency: Arc<AtomicUsize>,
+    reader: SpectrumReader,
+    taps: Taps,
+}
+
+impl Analyzer {
+    pub fn new(config: audioviz::Config, device: AudioDevice) -> Self {
+        let audio_stream = audioviz::AudioStream::init(
+            config.clone(),
+        );
+        let event_sender = audio_stream.get_event_sender();
+        let (device_sender, device_receiver) = mpsc::channel();
+        let taps: Taps = Arc::new(Mutex::new(Vec::new()));
+        audio::init_audio_sender(event_sender.clone(), device, device_receiver, taps.clone());
+        let refresh_rate = Arc::new(AtomicUsize::new(config.refresh_rate));
+        let max_frequency = Arc::new(AtomicUsize::new(config.max_frequency));
+        let reader = spectrum::init_spectrum_fetcher(event_sender.clone(), refresh_rate.clone(), max_frequency.clone());
+
+        Analyzer {
+            event_sender,
+            device_sender,
+            refresh_rate,
+            max_frequency,
+            reader,
+            taps,
+        }
+    }
+
+    // returns (input devices, output devices), indices match `AudioDevice`
+    pub fn devices() -> (Vec<String>, Vec<String>) {
+        audio::iter_audio_devices()
+    }
+
+    // newest frame since the last call, never blocks
+    pub fn latest(&mut self) -> Option<SpectrumFrame> {
+        self.reader.latest()
+    }
+
+    // a copy of every raw sample block, e.g. for level meters
+    pub fn tap(&self) -> mpsc::Receiver<Samples> {
+        let (sender, receiver) = mpsc::channel();
+        if let Ok(mut taps) = self.taps.lock() {
+            taps.push(sender);
+        }
+        receiver
+    }
+
+    // restarts the capture stream on another device
+    pub fn set_device(&self, device: AudioDevice) {
+        let _ = self.device_sender.send(device);
+    }
+
+    // how often spectra are fetched from audioviz, per second
+    pub fn set_refresh_rate(&self, rate: usize) {
+        self.refresh_rate.store(rate, Ordering::Relaxed);
+    }
+
+    // has to follow the max frequency of the audioviz config, it decides
+    // which frequencies `SpectrumFrame::ranges` assigns to the bars
+    pub fn set_max_frequency(&self, frequency: usize) {
+        self.max_frequency.store(frequency, Ordering::Relaxed);
+    }
+
+    // raw access to audioviz, e.g. to change its config with `Event::SendConfig`
+    pub fn event_sender(&self) -> mpsc::Sender<audioviz::Event> {
+        self.event_sender.clone()
+    }
+
+    pub fn device_sender(&self) -> mpsc::Sender<AudioDevice> {
+        self.device_sender.clone()
+    }
+}
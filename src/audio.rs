@@ -1,8 +1,28 @@
 use std::thread;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use colored::*;
 
+// frames per audio callback and sample rate of the current stream, read by the performance overlay
+static CALLBACK_FRAMES: AtomicUsize = AtomicUsize::new(0);
+static SAMPLE_RATE: AtomicUsize = AtomicUsize::new(0);
+// rms of the last callback, stored as the bits of an f32
+static RMS: AtomicU32 = AtomicU32::new(0);
+// whether the device in use is printed to stdout
+static PRINT_DEVICE: AtomicBool = AtomicBool::new(true);
+
+// raw interleaved samples of one audio callback
+#[derive(Debug, Clone)]
+pub struct Samples {
+    pub data: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: usize,
+}
+
+// everyone who wants the raw samples next to audioviz, closed receivers are removed
+pub type Taps = Arc<Mutex<Vec<mpsc::Sender<Samples>>>>;
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum AudioDevice {
@@ -37,9 +57,9 @@ impl PartialEq for AudioDevice {
 }
 impl Eq for AudioDevice {}
 
-pub fn init_audio_sender(event_sender: mpsc::Sender<audioviz::Event>, mut audio_device: AudioDevice, audio_device_receiver: mpsc::Receiver<AudioDevice>) {
+pub fn init_audio_sender(event_sender: mpsc::Sender<audioviz::Event>, mut audio_device: AudioDevice, audio_device_receiver: mpsc::Receiver<AudioDevice>, taps: Taps) {
     thread::spawn(move || loop {
-        let _stream = match stream_audio(event_sender.clone(), audio_device) {
+        let _stream = match stream_audio(event_sender.clone(), audio_device, taps.clone()) {
             Ok(s) => Some(s),
             Err(_) => None
         };
@@ -50,7 +70,7 @@ pub fn init_audio_sender(event_sender: mpsc::Sender<audioviz::Event>, mut audio_
     });
 }
 
-fn stream_audio(event_sender: mpsc::Sender<audioviz::Event>, audio_device: AudioDevice) -> Result<cpal::Stream, ()> {
+fn stream_audio(event_sender: mpsc::Sender<audioviz::Event>, audio_device: AudioDevice, taps: Taps) -> Result<cpal::Stream, ()> {
     let event_sender = event_sender.clone();
 
     let host = cpal::default_host();
@@ -66,12 +86,14 @@ fn stream_audio(event_sender: mpsc::Sender<audioviz::Event>, audio_device: Audio
         }
     };
 
-    match audio_device {
-        AudioDevice::Input(_) => {
-            println!("using input device: {}", device.name().unwrap().green().bold());
-        }
-        AudioDevice::Output(_) => {
-            println!("using output device: {}", device.name().unwrap().green().bold());
+    if PRINT_DEVICE.load(Ordering::Relaxed) {
+        match audio_device {
+            AudioDevice::Input(_) => {
+                println!("using input device: {}", device.name().unwrap().green().bold());
+            }
+            AudioDevice::Output(_) => {
+                println!("using output device: {}", device.name().unwrap().green().bold());
+            }
         }
     }
 
@@ -85,10 +107,19 @@ fn stream_audio(event_sender: mpsc::Sender<audioviz::Event>, audio_device: Audio
         }
     };
 
+    let channels = device_config.channels().max(1) as usize;
+    let sample_rate = device_config.sample_rate().0 as usize;
+    SAMPLE_RATE.store(sample_rate, Ordering::Relaxed);
+
     let stream = match device_config.sample_format() {
         cpal::SampleFormat::F32 => match device.build_input_stream(
             &device_config.into(),
-            move |data, _: &_| handle_input_data_f32(data, event_sender.clone()),
+            move |data: &[f32], _: &_| {
+                CALLBACK_FRAMES.store(data.len() / channels, Ordering::Relaxed);
+                store_rms(data);
+                send_to_taps(&taps, data, channels, sample_rate);
+                handle_input_data_f32(data, event_sender.clone())
+            },
             err_fn,
         ) {
             Ok(v) => v,
@@ -124,6 +155,47 @@ pub fn iter_audio_devices() -> (Vec<String>, Vec<String>) {
     (input_devices, output_devices)
 }
 
+// returns (frames per callback, sample rate), both 0 if no stream is running yet
+pub fn callback_info() -> (usize, usize) {
+    (CALLBACK_FRAMES.load(Ordering::Relaxed), SAMPLE_RATE.load(Ordering::Relaxed))
+}
+
+// frontends that draw to the terminal turn this off, the device
+// line would end up in the middle of their screen
+pub fn print_device(enabled: bool) {
+    PRINT_DEVICE.store(enabled, Ordering::Relaxed);
+}
+
+// rms over all channels of the last audio callback
+pub fn rms() -> f32 {
+    f32::from_bits(RMS.load(Ordering::Relaxed))
+}
+
+fn store_rms(data: &[f32]) {
+    if data.is_empty() {
+        return;
+    }
+    let rms = (data.iter().map(|s| s * s).sum::<f32>() / data.len() as f32).sqrt();
+    RMS.store(rms.to_bits(), Ordering::Relaxed);
+}
+
+fn send_to_taps(taps: &Taps, data: &[f32], channels: usize, sample_rate: usize) {
+    // never wait in the audio callback, a missed block only shows up as a small gap
+    let mut taps = match taps.try_lock() {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+    if taps.is_empty() {
+        return;
+    }
+    let samples = Samples {
+        data: data.to_vec(),
+        channels,
+        sample_rate,
+    };
+    taps.retain(|t| t.send(samples.clone()).is_ok());
+}
+
 fn handle_input_data_f32(data: &[f32], sender: mpsc::Sender<audioviz::Event>) {
     // sends the raw data to audio_stream via the event_sender
     sender.send(audioviz::Event::SendData(data.to_vec())).unwrap();
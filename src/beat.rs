@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// onset detection by spectral flux of the lowest bars, beats are flux peaks
+// above an adaptive threshold, the bpm is the median distance between beats
+pub struct BeatDetector {
+    pub sensitivity: f32, // standard deviations the flux has to exceed its mean
+    previous: Vec<f32>,
+    flux_history: VecDeque<f32>,
+    history_length: usize, // flux values in `FLUX_HISTORY` seconds
+    last_beat: Option<Instant>,
+    intervals: VecDeque<f32>, // seconds between the last beats
+    pulse: f32,
+    last_update: Instant,
+}
+
+// seconds of flux the threshold is taken from
+const FLUX_HISTORY: f32 = 1.0;
+const BPM_HISTORY: usize = 8;
+// nothing faster than 240 bpm counts as a new beat
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+// gaps longer than this are breaks, not beats
+const MAX_INTERVAL: f32 = 2.0;
+// seconds until the pulse decays from 1.0 to 0.0
+const PULSE_DECAY: f32 = 0.2;
+
+impl BeatDetector {
+    // spectra per second passed to `process`, keeps the flux history at `FLUX_HISTORY` seconds
+    pub fn set_refresh_rate(&mut self, rate: f32) {
+        self.history_length = ((rate * FLUX_HISTORY).round() as usize).max(4);
+        while self.flux_history.len() > self.history_length {
+            self.flux_history.pop_front();
+        }
+    }
+
+    // returns true once per beat
+    pub fn process(&mut self, bars: &[f32]) -> bool {
+        self.process_at(bars, Instant::now())
+    }
+
+    // `process` for a spectrum taken at `now`
+    pub fn process_at(&mut self, bars: &[f32], now: Instant) -> bool {
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+        self.pulse = (self.pulse - dt / PULSE_DECAY).max(0.0);
+
+        // roughly the bass range with the default frequency distribution
+        let low = &bars[..(bars.len() / 8).max(1).min(bars.len())];
+
+        // only rising energy counts, falling bars are no onset
+        let flux: f32 = if self.previous.len() == low.len() {
+            low.iter().zip(self.previous.iter()).map(|(v, p)| (v - p).max(0.0)).sum()
+        } else {
+            // the bar count changed, start over
+            self.flux_history.clear();
+            0.0
+        };
+        self.previous.clear();
+        self.previous.extend_from_slice(low);
+
+        let count = self.flux_history.len().max(1) as f32;
+        let mean = self.flux_history.iter().sum::<f32>() / count;
+        let variance = self.flux_history.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / count;
+        let threshold = mean + variance.sqrt() * self.sensitivity;
+
+        self.flux_history.push_back(flux);
+        if self.flux_history.len() > self.history_length {
+            self.flux_history.pop_front();
+        }
+
+        // needs some history before the threshold means anything
+        if self.flux_history.len() < self.history_length / 4 || flux <= threshold || flux < 0.001 {
+            return false;
+        }
+        if let Some(last) = self.last_beat {
+            let interval = now.duration_since(last);
+            if interval < MIN_INTERVAL {
+                return false;
+            }
+            let interval = interval.as_secs_f32();
+            if interval <= MAX_INTERVAL {
+                self.intervals.push_back(interval);
+                if self.intervals.len() > BPM_HISTORY {
+                    self.intervals.pop_front();
+                }
+            }
+        }
+        self.last_beat = Some(now);
+        self.pulse = 1.0;
+
+        true
+    }
+
+    // None until enough beats were detected
+    pub fn bpm(&self) -> Option<f32> {
+        if self.intervals.len() < 3 {
+            return None;
+        }
+        // the median ignores single missed or extra beats
+        let mut intervals: Vec<f32> = self.intervals.iter().copied().collect();
+        intervals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let median = intervals[intervals.len() / 2];
+
+        let mut bpm = 60.0 / median;
+        // fold into the range most music is written in
+        while bpm < 70.0 {
+            bpm *= 2.0;
+        }
+        while bpm > 180.0 {
+            bpm /= 2.0;
+        }
+        Some(bpm)
+    }
+
+    // 1.0 right after a beat, decays to 0.0
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+}
+
+impl Default for BeatDetector {
+    fn default() -> Self {
+        BeatDetector {
+            sensitivity: 1.5,
+            previous: Vec::new(),
+            flux_history: VecDeque::new(),
+            // the default refresh rate of 60 per second
+            history_length: 60,
+            last_beat: None,
+            intervals: VecDeque::new(),
+            pulse: 0.0,
+            last_update: Instant::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f32 = 60.0;
+
+    // 10 s of spectra at `RATE` with a bass hit every half second, 120 bpm
+    #[test]
+    fn pulse_train() {
+        let mut detector = BeatDetector::default();
+        detector.set_refresh_rate(RATE);
+        let start = Instant::now();
+
+        let mut beats = Vec::new();
+        for frame in 0..(10.0 * RATE) as usize {
+            let hit = frame % (RATE as usize / 2) == 0;
+            // a little movement everywhere, so the threshold is not just zero
+            let bars: Vec<f32> = (0..32)
+                .map(|i| if hit { 1.0 } else { 0.1 + 0.02 * ((frame * 7 + i * 3) % 5) as f32 })
+                .collect();
+            let now = start + Duration::from_secs_f32(frame as f32 / RATE);
+            if detector.process_at(&bars, now) {
+                beats.push(frame);
+            }
+        }
+
+        // every hit after the warm up is found and nothing else
+        let hits: Vec<usize> = (0..20).map(|b| b * RATE as usize / 2).filter(|f| *f >= RATE as usize / 4).collect();
+        assert_eq!(beats, hits);
+
+        let bpm = detector.bpm().expect("no bpm");
+        assert!((bpm - 120.0).abs() < 1.0, "bpm is {}", bpm);
+        assert!(detector.pulse() < 1.0);
+    }
+
+    #[test]
+    fn history_follows_the_refresh_rate() {
+        let mut detector = BeatDetector::default();
+        detector.set_refresh_rate(144.0);
+        assert_eq!(detector.history_length, 144);
+        detector.set_refresh_rate(0.5);
+        assert_eq!(detector.history_length, 4);
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+use iced::{canvas::Frame, Size};
+use crate::ui::bars::Bars;
+
+const ITERATIONS: u32 = 200;
+
+// measures how long building the bar geometry takes on the cpu,
+// run with `audiolizer --bench-draw`
+pub fn run() {
+    for count in &[100, 1000, 2000] {
+        let mut bars = Bars::default();
+        bars.data = (0..*count).map(|i| (i as f32 * 0.37).sin().abs()).collect();
+
+        // warm up allocations before measuring
+        draw(&bars);
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            draw(&bars);
+        }
+        let per_frame: Duration = start.elapsed() / ITERATIONS;
+
+        println!("{:>5} bars: {:>8.3} ms per frame", count, per_frame.as_secs_f64() * 1000.0);
+    }
+}
+
+fn draw(bars: &Bars) {
+    let mut frame = Frame::new(Size::new(1920.0, 1080.0));
+    bars.draw_bars(&mut frame);
+    let _ = frame.into_geometry();
+}
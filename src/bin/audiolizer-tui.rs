@@ -0,0 +1,330 @@
+use std::io::{self, Write};
+use std::time::Duration;
+use colored::*;
+use crossterm::{
+    cursor, execute, queue, terminal,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+};
+use gag::Gag;
+use audiolizer::{audio, Analyzer, AudioDevice};
+use audiolizer::smoothing::Smoother;
+
+// eighths of a cell, index 0 is an empty cell
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Theme {
+    Light,
+    Dark,
+    Midnight,
+}
+
+impl Theme {
+    const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::Midnight];
+
+    // same as the active color of the gui themes
+    fn color(&self) -> (u8, u8, u8) {
+        match self {
+            Theme::Light => (0x03, 0xA9, 0xF4),
+            Theme::Dark => (0x84, 0x4c, 0xb5),
+            Theme::Midnight => (0xab, 0xab, 0xab),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::Midnight => "midnight",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mirroring {
+    Off,
+    Center,
+    LeftRight,
+    TopBottom,
+}
+
+impl Mirroring {
+    fn next(&self) -> Self {
+        match self {
+            Mirroring::Off => Mirroring::Center,
+            Mirroring::Center => Mirroring::LeftRight,
+            Mirroring::LeftRight => Mirroring::TopBottom,
+            Mirroring::TopBottom => Mirroring::Off,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Mirroring::Off => "off",
+            Mirroring::Center => "center",
+            Mirroring::LeftRight => "left/right",
+            Mirroring::TopBottom => "top/bottom",
+        }
+    }
+}
+
+struct Tui {
+    analyzer: Analyzer,
+    smoother: Smoother,
+    config: audioviz::Config, // cached like in the gui settings
+    volume: f32,              // slider value of the gui, `config.volume` is volume² / 100
+    devices: Vec<(AudioDevice, String)>,
+    device: usize,
+    theme: Theme,
+    mirroring: Mirroring,
+    data: Vec<f32>,
+}
+
+impl Tui {
+    fn new(theme: Theme, mirroring: Mirroring, bar_count: Option<usize>) -> Self {
+        let mut config = audioviz::Config {
+            ..Default::default()
+        };
+        if let Some(count) = bar_count {
+            config.bar_count = count;
+        }
+
+        let (input_devices, output_devices) = Analyzer::devices();
+        let devices = output_devices.into_iter().enumerate()
+            .map(|(i, name)| (AudioDevice::Output(i), name))
+            .chain(input_devices.into_iter().enumerate().map(|(i, name)| (AudioDevice::Input(i), name)))
+            .collect();
+
+        Tui {
+            analyzer: Analyzer::new(config.clone(), AudioDevice::Output(0)),
+            smoother: Default::default(),
+            volume: (config.volume * 100.0).sqrt(),
+            config,
+            devices,
+            device: 0,
+            theme,
+            mirroring,
+            data: Vec::new(),
+        }
+    }
+
+    fn send_config(&mut self, config: audioviz::Config) {
+        self.config = config.clone();
+        let _ = self.analyzer.event_sender().send(audioviz::Event::SendConfig(config));
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.max(1.0).min(150.0);
+        let config = audioviz::Config {
+            volume: self.volume.powi(2) * 0.01,
+            ..self.config.clone()
+        };
+        self.send_config(config);
+    }
+
+    fn set_bar_count(&mut self, count: isize) {
+        let count = count.max(1).min(2000) as usize;
+        if count == self.config.bar_count {
+            return;
+        }
+        let config = audioviz::Config {
+            bar_count: count,
+            ..self.config.clone()
+        };
+        let _ = self.analyzer.event_sender().send(audioviz::Event::ClearBuffer);
+        self.send_config(config);
+    }
+
+    fn cycle_device(&mut self, step: isize) {
+        if self.devices.is_empty() {
+            return;
+        }
+        let len = self.devices.len() as isize;
+        self.device = ((self.device as isize + step).rem_euclid(len)) as usize;
+        self.analyzer.set_device(self.devices[self.device].0);
+    }
+
+    // returns false if the user wants to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            // raw mode delivers ctrl+c as a key instead of a signal
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up => self.set_volume(self.volume + 5.0),
+            KeyCode::Down => self.set_volume(self.volume - 5.0),
+            KeyCode::Right => self.set_bar_count(self.config.bar_count as isize + 10),
+            KeyCode::Left => self.set_bar_count(self.config.bar_count as isize - 10),
+            KeyCode::Char('n') => self.cycle_device(1),
+            KeyCode::Char('p') => self.cycle_device(-1),
+            KeyCode::Char('m') => self.mirroring = self.mirroring.next(),
+            KeyCode::Char('t') => {
+                let i = Theme::ALL.iter().position(|t| *t == self.theme).unwrap_or(0);
+                self.theme = Theme::ALL[(i + 1) % Theme::ALL.len()];
+            }
+            _ => (),
+        }
+        true
+    }
+
+    fn update(&mut self) {
+        if let Some(frame) = self.analyzer.latest() {
+            let mut data = frame.bars;
+            self.smoother.process(&mut data);
+            self.data = data;
+        }
+    }
+
+    // value of every terminal column and whether it grows from the vertical center,
+    // follows the same slot layout as `Bars::for_each_bar`
+    fn columns(&self, width: usize) -> Vec<(f32, bool)> {
+        let count = self.data.len();
+        if count == 0 {
+            return vec![(0.0, false); width];
+        }
+
+        (0..width).map(|x| {
+            match self.mirroring {
+                Mirroring::Off => (self.data[x * count / width], false),
+                Mirroring::TopBottom => (self.data[x * count / width], true),
+                Mirroring::Center => {
+                    let slot = x * count * 2 / width;
+                    let i = if slot < count { count - 1 - slot } else { slot - count };
+                    (self.data[i], false)
+                }
+                Mirroring::LeftRight => {
+                    let slot = x * count * 2 / width;
+                    let i = if slot < count { slot } else { count * 2 - 1 - slot };
+                    (self.data[i], false)
+                }
+            }
+        }).collect()
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let width = width as usize;
+        // last line is the status line
+        let rows = height.saturating_sub(1) as usize;
+        let columns = self.columns(width);
+        let (r, g, b) = self.theme.color();
+
+        for row in 0..rows {
+            let line: String = columns.iter().map(|&(value, from_center)| {
+                let value = value.max(0.0).min(1.0);
+                if from_center {
+                    // full cells only, half of the height in each direction
+                    let center = rows as f32 / 2.0;
+                    let extent = value * center;
+                    let distance = (row as f32 + 0.5 - center).abs();
+                    if distance < extent { BLOCKS[8] } else { BLOCKS[0] }
+                } else {
+                    let filled = value * rows as f32 - (rows - 1 - row) as f32;
+                    let eighths = (filled.max(0.0).min(1.0) * 8.0).round() as usize;
+                    BLOCKS[eighths]
+                }
+            }).collect();
+
+            queue!(out, cursor::MoveTo(0, row as u16))?;
+            write!(out, "{}", line.truecolor(r, g, b))?;
+        }
+
+        let device = self.devices.get(self.device).map(|d| d.1.as_str()).unwrap_or("none");
+        let status = format!(
+            " {} | volume {:.0} | bars {} | mirroring {} | theme {} | ←→ bars ↑↓ volume n/p device m mirroring t theme q quit",
+            device,
+            self.volume,
+            self.config.bar_count,
+            self.mirroring.name(),
+            self.theme.name(),
+        );
+        let status: String = status.chars().take(width).collect();
+        queue!(
+            out,
+            cursor::MoveTo(0, rows as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+        )?;
+        write!(out, "{}", status.bold())?;
+
+        out.flush()
+    }
+}
+
+fn parse_args() -> (Theme, Mirroring, Option<usize>) {
+    let mut theme = Theme::Dark;
+    let mut mirroring = Mirroring::Center;
+    let mut bar_count = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--theme" => match args.next().as_deref() {
+                Some("light") => theme = Theme::Light,
+                Some("dark") => theme = Theme::Dark,
+                Some("midnight") => theme = Theme::Midnight,
+                v => println!("unknown theme: {}", v.unwrap_or("").red().bold()),
+            },
+            "--bars" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                // same range as the slider of the gui
+                Some(v) => bar_count = Some(v.max(1).min(2000)),
+                None => println!("{} expects a number", "--bars".red().bold()),
+            },
+            "--mirroring" => mirroring = Mirroring::Center,
+            "--no-mirroring" => mirroring = Mirroring::Off,
+            _ => println!("unknown argument: {}", arg.red().bold()),
+        }
+    }
+
+    (theme, mirroring, bar_count)
+}
+
+// gives the terminal back when dropped, also when main panics
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(stdout: &mut io::Stdout) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let guard = RawTerminal;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() -> io::Result<()> {
+    let (theme, mirroring, bar_count) = parse_args();
+
+    // alsa and jack would write over the bars otherwise
+    let _print_gag = Gag::stderr().unwrap();
+    // the status line shows the device instead
+    audio::print_device(false);
+
+    let mut tui = Tui::new(theme, mirroring, bar_count);
+
+    let mut stdout = io::stdout();
+    let _terminal = RawTerminal::enter(&mut stdout)?;
+
+    loop {
+        // waiting for input doubles as the frame timer, ~60 fps
+        if event::poll(Duration::from_millis(16))? {
+            match event::read()? {
+                Event::Key(key) => {
+                    if !tui.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+                Event::Resize(_, _) => {
+                    queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+                }
+                _ => (),
+            }
+        }
+        tui.update();
+        tui.draw(&mut stdout)?;
+    }
+}
@@ -0,0 +1,171 @@
+// lets mouse clicks pass through the window to whatever is behind it, for
+// overlays. iced doesn't hand out its window, so it is looked up by its title
+// among the windows of this process
+pub fn set(title: &str, enabled: bool) -> Result<(), String> {
+    platform::set(title, enabled)
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, TRUE};
+    use winapi::shared::windef::HWND;
+    use winapi::um::processthreadsapi::GetCurrentProcessId;
+    use winapi::um::winuser::{
+        EnumWindows, GetWindowLongPtrW, GetWindowTextW, GetWindowThreadProcessId,
+        SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED, WS_EX_TRANSPARENT,
+    };
+
+    struct Search {
+        title: Vec<u16>,
+        found: HWND,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, search: LPARAM) -> BOOL {
+        let search = &mut *(search as *mut Search);
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid != GetCurrentProcessId() {
+            return TRUE;
+        }
+        let mut title = [0u16; 256];
+        let length = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
+        if title[..length.max(0) as usize] == search.title[..] {
+            search.found = hwnd;
+            return FALSE;
+        }
+        TRUE
+    }
+
+    pub fn set(title: &str, enabled: bool) -> Result<(), String> {
+        let mut search = Search {
+            title: OsStr::new(title).encode_wide().collect(),
+            found: ptr::null_mut(),
+        };
+        unsafe {
+            EnumWindows(Some(visit), &mut search as *mut Search as LPARAM);
+            if search.found.is_null() {
+                return Err(String::from("the window was not found"));
+            }
+            let style = GetWindowLongPtrW(search.found, GWL_EXSTYLE);
+            if enabled {
+                let flags = (WS_EX_LAYERED | WS_EX_TRANSPARENT) as isize;
+                SetWindowLongPtrW(search.found, GWL_EXSTYLE, style | flags);
+                // a layered window stays invisible until its opacity is set
+                SetLayeredWindowAttributes(search.found, 0, 255, LWA_ALPHA);
+            } else {
+                SetWindowLongPtrW(search.found, GWL_EXSTYLE, style & !(WS_EX_TRANSPARENT as isize));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int, c_uchar, c_ulong, c_void};
+    use std::ptr;
+    use x11_dl::xfixes::Xlib as XFixes;
+    use x11_dl::xlib::{self, Display, Window, Xlib};
+
+    // `ShapeInput` of X11/extensions/shape.h
+    const SHAPE_INPUT: c_int = 2;
+
+    pub fn set(title: &str, enabled: bool) -> Result<(), String> {
+        let xlib = Xlib::open().map_err(|e| e.to_string())?;
+        let xfixes = XFixes::open().map_err(|e| e.to_string())?;
+        unsafe {
+            let display = (xlib.XOpenDisplay)(ptr::null());
+            if display.is_null() {
+                return Err(String::from("no X11 display"));
+            }
+            let root = (xlib.XDefaultRootWindow)(display);
+            let result = match find(&xlib, display, root, title) {
+                Some(window) => {
+                    // an empty input region lets every click through, no region restores the default
+                    if enabled {
+                        let region = (xfixes.XFixesCreateRegion)(display, ptr::null_mut(), 0);
+                        (xfixes.XFixesSetWindowShapeRegion)(display, window, SHAPE_INPUT, 0, 0, region);
+                        (xfixes.XFixesDestroyRegion)(display, region);
+                    } else {
+                        (xfixes.XFixesSetWindowShapeRegion)(display, window, SHAPE_INPUT, 0, 0, 0);
+                    }
+                    (xlib.XFlush)(display);
+                    Ok(())
+                }
+                // e.g. on wayland, where the X11 display only holds xwayland clients
+                None => Err(String::from("the window was not found on the X11 display")),
+            };
+            (xlib.XCloseDisplay)(display);
+            result
+        }
+    }
+
+    // depth first, window managers reparent the window into their frame
+    unsafe fn find(xlib: &Xlib, display: *mut Display, window: Window, title: &str) -> Option<Window> {
+        if is_ours(xlib, display, window, title) {
+            return Some(window);
+        }
+        let (mut root, mut parent) = (0, 0);
+        let mut children: *mut Window = ptr::null_mut();
+        let mut count = 0;
+        if (xlib.XQueryTree)(display, window, &mut root, &mut parent, &mut children, &mut count) == 0 || children.is_null() {
+            return None;
+        }
+        let found = std::slice::from_raw_parts(children, count as usize)
+            .iter()
+            .find_map(|child| find(xlib, display, *child, title));
+        (xlib.XFree)(children as *mut c_void);
+        found
+    }
+
+    unsafe fn is_ours(xlib: &Xlib, display: *mut Display, window: Window, title: &str) -> bool {
+        let mut name: *mut c_char = ptr::null_mut();
+        if (xlib.XFetchName)(display, window, &mut name) == 0 || name.is_null() {
+            return false;
+        }
+        let matches = CStr::from_ptr(name).to_bytes() == title.as_bytes();
+        (xlib.XFree)(name as *mut c_void);
+        // another audiolizer has the same title, a window without a pid is taken as ours
+        let ours = std::process::id();
+        matches && pid(xlib, display, window).unwrap_or(ours) == ours
+    }
+
+    unsafe fn pid(xlib: &Xlib, display: *mut Display, window: Window) -> Option<u32> {
+        let atom = (xlib.XInternAtom)(display, b"_NET_WM_PID\0".as_ptr() as *const c_char, xlib::True);
+        if atom == 0 {
+            return None;
+        }
+        let mut actual_type = 0;
+        let mut format = 0;
+        let mut items: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = ptr::null_mut();
+        let status = (xlib.XGetWindowProperty)(
+            display, window, atom, 0, 1, xlib::False, xlib::XA_CARDINAL,
+            &mut actual_type, &mut format, &mut items, &mut bytes_after, &mut data,
+        );
+        if status != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+        // 32 bit properties are returned as longs
+        let pid = if format == 32 && items == 1 {
+            Some(*(data as *const c_ulong) as u32)
+        } else {
+            None
+        };
+        (xlib.XFree)(data as *mut c_void);
+        pid
+    }
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+mod platform {
+    pub fn set(_title: &str, _enabled: bool) -> Result<(), String> {
+        Err(String::from("click-through is only supported on Windows and X11"))
+    }
+}
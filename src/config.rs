@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use colored::*;
+use audiolizer::stream::StreamFormat;
+
+// where the settings panel is placed when it is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsLayout {
+    // below the bars, the canvas loses height
+    Bottom,
+    // in a fixed width column next to the bars, the canvas keeps its full height
+    Side,
+    // in a window of its own, the canvas keeps the whole main window
+    Window,
+}
+
+impl SettingsLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SettingsLayout::Bottom => "bottom",
+            SettingsLayout::Side => "side",
+            SettingsLayout::Window => "window",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "bottom" => Some(SettingsLayout::Bottom),
+            "side" => Some(SettingsLayout::Side),
+            "window" => Some(SettingsLayout::Window),
+            _ => None,
+        }
+    }
+}
+
+// settings that survive a restart, stored as `key = value` lines
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub fullscreen: bool,
+    pub borderless: bool,
+    pub always_on_top: bool,
+    pub hide_settings_button: bool,
+    pub transparent: bool,
+    pub click_through: bool,
+    pub settings_layout: SettingsLayout,
+    // Hz between the bass, mid and treble meters
+    pub crossover_low: f32,
+    pub crossover_high: f32,
+    // spectrum server on localhost, see `audiolizer::stream`
+    pub stream: bool,
+    pub stream_port: u16,
+    pub stream_rate: u32,
+    pub stream_format: StreamFormat,
+    // osc output over udp, see `audiolizer::osc`
+    pub osc: bool,
+    pub osc_target: String,
+    // midi cc output, only used when built with the `midi` feature
+    pub midi: bool,
+    pub midi_port: String,  // part of the port name or `virtual`
+    pub midi_bands: String, // comma separated `low-high:channel:controller`
+    // http remote control on localhost
+    pub remote: bool,
+    pub remote_port: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            fullscreen: false,
+            borderless: false,
+            always_on_top: false,
+            hide_settings_button: false,
+            transparent: false,
+            click_through: false,
+            settings_layout: SettingsLayout::Bottom,
+            crossover_low: 250.0,
+            crossover_high: 4000.0,
+            stream: false,
+            stream_port: 7700,
+            stream_rate: 60,
+            stream_format: StreamFormat::Json,
+            osc: false,
+            osc_target: String::from("127.0.0.1:9000"),
+            midi: false,
+            midi_port: String::from("virtual"),
+            midi_bands: String::from("0-0.1:1:20, 0.1-0.5:1:21, 0.5-1:1:22"),
+            remote: false,
+            remote_port: 7701,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let mut config = Config::default();
+        let content = match path().and_then(|p| fs::read_to_string(p).ok()) {
+            Some(c) => c,
+            None => return config,
+        };
+        let values = parse(&content);
+
+        read_bool(&values, "fullscreen", &mut config.fullscreen);
+        read_bool(&values, "borderless", &mut config.borderless);
+        read_bool(&values, "always_on_top", &mut config.always_on_top);
+        read_bool(&values, "hide_settings_button", &mut config.hide_settings_button);
+        read_bool(&values, "transparent", &mut config.transparent);
+        read_bool(&values, "click_through", &mut config.click_through);
+        if let Some(layout) = values.get("settings_layout").and_then(|v| SettingsLayout::parse(v)) {
+            config.settings_layout = layout;
+        }
+        read_value(&values, "crossover_low", &mut config.crossover_low);
+        read_value(&values, "crossover_high", &mut config.crossover_high);
+        read_bool(&values, "stream", &mut config.stream);
+        read_value(&values, "stream_port", &mut config.stream_port);
+        read_value(&values, "stream_rate", &mut config.stream_rate);
+        if let Some(format) = values.get("stream_format").and_then(|v| StreamFormat::parse(v)) {
+            config.stream_format = format;
+        }
+        read_bool(&values, "osc", &mut config.osc);
+        read_value(&values, "osc_target", &mut config.osc_target);
+        read_bool(&values, "midi", &mut config.midi);
+        read_value(&values, "midi_port", &mut config.midi_port);
+        read_value(&values, "midi_bands", &mut config.midi_bands);
+        read_bool(&values, "remote", &mut config.remote);
+        read_value(&values, "remote_port", &mut config.remote_port);
+
+        config
+    }
+
+    pub fn save(&self) {
+        let path = match path() {
+            Some(p) => p,
+            None => return,
+        };
+        let content = format!(
+            "fullscreen = {}\nborderless = {}\nalways_on_top = {}\nhide_settings_button = {}\ntransparent = {}\nclick_through = {}\nsettings_layout = {}\ncrossover_low = {}\ncrossover_high = {}\nstream = {}\nstream_port = {}\nstream_rate = {}\nstream_format = {}\nosc = {}\nosc_target = {}\nmidi = {}\nmidi_port = {}\nmidi_bands = {}\nremote = {}\nremote_port = {}\n",
+            self.fullscreen,
+            self.borderless,
+            self.always_on_top,
+            self.hide_settings_button,
+            self.transparent,
+            self.click_through,
+            self.settings_layout.name(),
+            self.crossover_low,
+            self.crossover_high,
+            self.stream,
+            self.stream_port,
+            self.stream_rate,
+            self.stream_format.name(),
+            self.osc,
+            self.osc_target,
+            self.midi,
+            self.midi_port,
+            self.midi_bands,
+            self.remote,
+            self.remote_port,
+        );
+
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(&path, content) {
+            println!("could not save config to {}: {}", path.display().to_string().yellow(), e);
+        }
+    }
+
+    // command line flags override the stored values for this run only
+    pub fn apply_args(&mut self, args: impl Iterator<Item = String>) {
+        for arg in args {
+            match arg.as_str() {
+                "--fullscreen" => self.fullscreen = true,
+                "--windowed" => self.fullscreen = false,
+                "--borderless" => self.borderless = true,
+                "--decorated" => self.borderless = false,
+                "--always-on-top" => self.always_on_top = true,
+                "--no-always-on-top" => self.always_on_top = false,
+                "--hide-settings-button" => self.hide_settings_button = true,
+                "--show-settings-button" => self.hide_settings_button = false,
+                "--transparent" => self.transparent = true,
+                "--opaque" => self.transparent = false,
+                "--click-through" => self.click_through = true,
+                "--no-click-through" => self.click_through = false,
+                "--settings-side" => self.settings_layout = SettingsLayout::Side,
+                "--settings-bottom" => self.settings_layout = SettingsLayout::Bottom,
+                "--settings-window" => self.settings_layout = SettingsLayout::Window,
+                "--stream" => self.stream = true,
+                "--no-stream" => self.stream = false,
+                "--osc" => self.osc = true,
+                "--no-osc" => self.osc = false,
+                "--midi" => self.midi = true,
+                "--no-midi" => self.midi = false,
+                "--remote" => self.remote = true,
+                "--no-remote" => self.remote = false,
+                _ => {
+                    // flags with a value are written as `--flag=value`
+                    let (flag, value) = match arg.split_once('=') {
+                        Some(v) => v,
+                        None => {
+                            println!("unknown argument: {}", arg.red().bold());
+                            continue;
+                        }
+                    };
+                    let valid = match flag {
+                        "--stream-port" => value.parse().map(|v| self.stream_port = v).is_ok(),
+                        "--stream-rate" => value.parse().map(|v| self.stream_rate = v).is_ok(),
+                        "--stream-format" => StreamFormat::parse(value).map(|v| self.stream_format = v).is_some(),
+                        "--osc-target" => {
+                            self.osc_target = value.to_string();
+                            true
+                        }
+                        "--remote-port" => value.parse().map(|v| self.remote_port = v).is_ok(),
+                        "--midi-port" => {
+                            self.midi_port = value.to_string();
+                            true
+                        }
+                        "--midi-bands" => {
+                            self.midi_bands = value.to_string();
+                            true
+                        }
+                        _ => {
+                            println!("unknown argument: {}", arg.red().bold());
+                            continue;
+                        }
+                    };
+                    if !valid {
+                        println!("invalid value for {}: {}", flag.red().bold(), value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dir().map(|d| d.join("config"))
+}
+
+// platform config directory of audiolizer, also holds the keymap
+pub fn dir() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+    };
+    dir.map(|d| d.join("audiolizer"))
+}
+
+pub fn parse(content: &str) -> HashMap<String, String> {
+    content.lines()
+        .filter(|l| !l.trim().starts_with('#'))
+        .filter_map(|l| {
+            let mut split = l.splitn(2, '=');
+            let key = split.next()?.trim();
+            let value = split.next()?.trim();
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn read_bool(values: &HashMap<String, String>, key: &str, target: &mut bool) {
+    read_value(values, key, target);
+}
+
+fn read_value<T: std::str::FromStr>(values: &HashMap<String, String>, key: &str, target: &mut T) {
+    if let Some(v) = values.get(key).and_then(|v| v.parse().ok()) {
+        *target = v;
+    }
+}
@@ -0,0 +1,391 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command as Process, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use iced::{executor, Application, Command, Container, Element, Length, Subscription};
+use iced::futures::channel::mpsc as channel;
+use iced::futures::stream::{BoxStream, StreamExt};
+use audiolizer::AudioDevice;
+use gag::Gag;
+use crate::config::{self, SettingsLayout};
+use crate::theme::Theme;
+use crate::ui::bars::{BeatReaction, MirrorAxis, Scaling};
+use crate::ui::settings::{Field, SettingMessage, Settings};
+
+// iced drives a single window per process, so the detached settings are
+// audiolizer started a second time with this flag. both processes send every
+// changed setting as one line of text over the pipes of the child
+pub const CHILD_FLAG: &str = "--settings-child";
+
+const MIRROR_AXES: [(&str, MirrorAxis); 3] = [
+    ("center", MirrorAxis::Center),
+    ("left_right", MirrorAxis::LeftRight),
+    ("top_bottom", MirrorAxis::TopBottom),
+];
+const SCALINGS: [(&str, Scaling); 2] = [
+    ("linear", Scaling::Linear),
+    ("decibel", Scaling::Decibel),
+];
+const BEAT_REACTIONS: [(&str, BeatReaction); 3] = [
+    ("off", BeatReaction::Off),
+    ("background", BeatReaction::Background),
+    ("flash", BeatReaction::Flash),
+];
+
+// the settings window as seen from the main window, closed when dropped
+pub struct SettingsWindow {
+    child: Child,
+    stdin: ChildStdin,
+    id: u64,
+    stdout: Arc<Mutex<Option<ChildStdout>>>,
+}
+
+impl SettingsWindow {
+    // starts the window with `state` as its settings
+    pub fn open(state: &[SettingMessage]) -> io::Result<Self> {
+        // tells the subscriptions of windows opened one after another apart
+        static OPENED: AtomicU64 = AtomicU64::new(0);
+
+        let mut child = Process::new(std::env::current_exe()?)
+            .arg(CHILD_FLAG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let pipes = child.stdin.take().zip(child.stdout.take());
+        let (stdin, stdout) = match pipes {
+            Some(p) => p,
+            None => {
+                let _ = child.kill();
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the settings window has no pipes"));
+            }
+        };
+
+        let mut window = SettingsWindow {
+            child,
+            stdin,
+            id: OPENED.fetch_add(1, Ordering::Relaxed),
+            stdout: Arc::new(Mutex::new(Some(stdout))),
+        };
+        for message in state {
+            window.send(message);
+        }
+        Ok(window)
+    }
+
+    // shows a setting that was changed somewhere else in the window
+    pub fn send(&mut self, message: &SettingMessage) {
+        if let Some(line) = encode(message) {
+            // a window that was closed is noticed by the subscription
+            let _ = writeln!(self.stdin, "{}", line);
+        }
+    }
+
+    // every setting changed in the window, `None` once it is closed
+    pub fn subscription(&self) -> Subscription<Option<SettingMessage>> {
+        Subscription::from_recipe(Lines { id: self.id, source: self.stdout.clone() })
+    }
+}
+
+impl Drop for SettingsWindow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// the decoded lines of `source`, followed by `None` when it ends
+struct Lines<R> {
+    id: u64,
+    source: Arc<Mutex<Option<R>>>,
+}
+
+impl<H: std::hash::Hasher, E, R: Read + Send + 'static> iced_native::subscription::Recipe<H, E> for Lines<R> {
+    type Output = Option<SettingMessage>;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Self::Output> {
+        let (sender, receiver) = channel::unbounded();
+
+        // a pipe can only be read once, the recipe is only streamed once per id
+        if let Some(source) = self.source.lock().ok().and_then(|mut s| s.take()) {
+            thread::spawn(move || {
+                for line in BufReader::new(source).lines() {
+                    let line = match line {
+                        Ok(l) => l,
+                        Err(_) => break,
+                    };
+                    if let Some(message) = decode(&line) {
+                        if sender.unbounded_send(Some(message)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                let _ = sender.unbounded_send(None);
+            });
+        }
+
+        receiver.boxed()
+    }
+}
+
+// `None` for messages that only concern the window they come from, like the selected tab
+pub fn encode(message: &SettingMessage) -> Option<String> {
+    if let Some((field, value)) = Field::of(message) {
+        return Some(format!("set {} {}", field.name(), value));
+    }
+
+    let line = match message {
+        SettingMessage::ThemeChanged(t) => format!("theme {}", t.name()),
+        SettingMessage::MirrorAxisChanged(a) => format!("mirror_axis {}", name_of(&MIRROR_AXES, *a)),
+        SettingMessage::ScalingChanged(s) => format!("scaling {}", name_of(&SCALINGS, *s)),
+        SettingMessage::BeatReactionChanged(r) => format!("beat_reaction {}", name_of(&BEAT_REACTIONS, *r)),
+        SettingMessage::SettingsLayoutChanged(l) => format!("settings_layout {}", l.name()),
+        SettingMessage::AudioDeviceChanged(AudioDevice::Output(i)) => format!("device output {}", i),
+        SettingMessage::AudioDeviceChanged(AudioDevice::Input(i)) => format!("device input {}", i),
+        SettingMessage::Mirroring(b) => format!("mirroring {}", b),
+        SettingMessage::AutoGain(b) => format!("auto_gain {}", b),
+        SettingMessage::Interpolation(b) => format!("interpolation {}", b),
+        SettingMessage::PerformanceOverlay(b) => format!("performance_overlay {}", b),
+        SettingMessage::ShowBpm(b) => format!("show_bpm {}", b),
+        SettingMessage::ShowPitch(b) => format!("show_pitch {}", b),
+        SettingMessage::Fullscreen(b) => format!("fullscreen {}", b),
+        SettingMessage::Borderless(b) => format!("borderless {}", b),
+        SettingMessage::AlwaysOnTop(b) => format!("always_on_top {}", b),
+        SettingMessage::HideSettingsButton(b) => format!("hide_settings_button {}", b),
+        SettingMessage::Transparent(b) => format!("transparent {}", b),
+        SettingMessage::ClickThrough(b) => format!("click_through {}", b),
+        SettingMessage::Meters(b) => format!("meters {}", b),
+        SettingMessage::Loudness(b) => format!("loudness {}", b),
+        SettingMessage::ResetAll => String::from("reset_all"),
+        _ => return None,
+    };
+    Some(line)
+}
+
+pub fn decode(line: &str) -> Option<SettingMessage> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let message = match words.as_slice() {
+        ["set", name, value] => {
+            let field = Field::ALL.iter().find(|f| f.name() == *name)?;
+            field.message(value.parse().ok().filter(|v: &f32| v.is_finite())?)
+        }
+        ["theme", name] => SettingMessage::ThemeChanged(*Theme::ALL.iter().find(|t| t.name() == *name)?),
+        ["mirror_axis", name] => SettingMessage::MirrorAxisChanged(value_of(&MIRROR_AXES, name)?),
+        ["scaling", name] => SettingMessage::ScalingChanged(value_of(&SCALINGS, name)?),
+        ["beat_reaction", name] => SettingMessage::BeatReactionChanged(value_of(&BEAT_REACTIONS, name)?),
+        ["settings_layout", name] => SettingMessage::SettingsLayoutChanged(SettingsLayout::parse(name)?),
+        ["device", "output", i] => SettingMessage::AudioDeviceChanged(AudioDevice::Output(i.parse().ok()?)),
+        ["device", "input", i] => SettingMessage::AudioDeviceChanged(AudioDevice::Input(i.parse().ok()?)),
+        ["reset_all"] => SettingMessage::ResetAll,
+        [name, value] => {
+            let b: bool = value.parse().ok()?;
+            match *name {
+                "mirroring" => SettingMessage::Mirroring(b),
+                "auto_gain" => SettingMessage::AutoGain(b),
+                "interpolation" => SettingMessage::Interpolation(b),
+                "performance_overlay" => SettingMessage::PerformanceOverlay(b),
+                "show_bpm" => SettingMessage::ShowBpm(b),
+                "show_pitch" => SettingMessage::ShowPitch(b),
+                "fullscreen" => SettingMessage::Fullscreen(b),
+                "borderless" => SettingMessage::Borderless(b),
+                "always_on_top" => SettingMessage::AlwaysOnTop(b),
+                "hide_settings_button" => SettingMessage::HideSettingsButton(b),
+                "transparent" => SettingMessage::Transparent(b),
+                "click_through" => SettingMessage::ClickThrough(b),
+                "meters" => SettingMessage::Meters(b),
+                "loudness" => SettingMessage::Loudness(b),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(message)
+}
+
+fn name_of<T: PartialEq>(names: &[(&'static str, T)], value: T) -> &'static str {
+    names.iter().find(|(_, v)| *v == value).map(|(n, _)| *n).unwrap_or("")
+}
+
+fn value_of<T: Copy>(names: &[(&str, T)], name: &str) -> Option<T> {
+    names.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+
+// entry point of the child process
+pub fn run() -> iced::Result {
+    // stdout belongs to the main window, alsa and jack errors stay hidden like there
+    let _print_gag = Gag::stderr().unwrap();
+
+    Detached::run(iced::Settings {
+        antialiasing: true,
+        window: iced::window::Settings {
+            size: (560, 700),
+            min_size: Some((300, 200)),
+            ..iced::window::Settings::default()
+        },
+        flags: (),
+        ..iced::Settings::default()
+    })
+}
+
+struct Detached {
+    theme: Theme,
+    settings: Settings,
+    input: Arc<Mutex<Option<io::Stdin>>>,
+    closed: bool,
+    // the main window talks to audioviz, what the settings send here is dropped
+    events: mpsc::Receiver<audioviz::Event>,
+    devices: mpsc::Receiver<AudioDevice>,
+}
+
+#[derive(Debug, Clone)]
+enum DetachedMessage {
+    Setting(SettingMessage),
+    Main(Option<SettingMessage>),
+}
+
+impl Detached {
+    fn apply(&mut self, msg: SettingMessage) {
+        if let SettingMessage::ThemeChanged(t) = msg {
+            self.theme = t;
+        }
+        self.settings.update(msg);
+    }
+
+    // applies a setting changed in this window and hands it to the main window
+    fn change(&mut self, msg: SettingMessage) {
+        if let Some(line) = encode(&msg) {
+            // println would panic once the main window is gone
+            let _ = writeln!(io::stdout(), "{}", line);
+        }
+        self.apply(msg);
+    }
+}
+
+impl Application for Detached {
+    type Executor = executor::Default;
+    type Message = DetachedMessage;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<DetachedMessage>) {
+        let (event_sender, events) = mpsc::channel();
+        let (device_sender, devices) = mpsc::channel();
+        let settings = Settings::new(event_sender, Theme::default(), audioviz::Config::default(), device_sender, &config::Config::load());
+
+        (
+            Detached {
+                theme: Theme::default(),
+                settings,
+                input: Arc::new(Mutex::new(Some(io::stdin()))),
+                closed: false,
+                events,
+                devices,
+            },
+            Command::none(),
+        )
+    }
+
+    fn title(&self) -> String {
+        String::from("Audiolizer settings")
+    }
+
+    fn update(&mut self, message: DetachedMessage) -> Command<DetachedMessage> {
+        while self.events.try_recv().is_ok() {}
+        while self.devices.try_recv().is_ok() {}
+
+        match message {
+            DetachedMessage::Main(Some(msg)) => self.apply(msg),
+            // the main window was closed or hid the settings
+            DetachedMessage::Main(None) => self.closed = true,
+            DetachedMessage::Setting(msg) => match msg {
+                // resolved here, the text of the rows only exists in this window
+                SettingMessage::InputSubmitted(field) => {
+                    if let Some(msg) = self.settings.submit(field) {
+                        self.change(msg);
+                    }
+                }
+                SettingMessage::Reset(field) => {
+                    let msg = self.settings.reset(field);
+                    self.change(msg);
+                }
+                SettingMessage::ResetAll => {
+                    for msg in self.settings.reset_all() {
+                        self.apply(msg);
+                    }
+                    self.change(msg);
+                }
+                msg => self.change(msg),
+            },
+        }
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<DetachedMessage> {
+        Subscription::from_recipe(Lines { id: 0, source: self.input.clone() })
+            .map(DetachedMessage::Main)
+    }
+
+    fn should_exit(&self) -> bool {
+        self.closed
+    }
+
+    fn view(&mut self) -> Element<DetachedMessage> {
+        Container::new(self.settings.view().map(DetachedMessage::Setting))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(2)
+            .style(self.theme)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let messages = vec![
+            SettingMessage::Volume(37.5),
+            SettingMessage::DbFloor(-60.0),
+            SettingMessage::BarWidthChanged(0.1),
+            SettingMessage::ThemeChanged(Theme::Midnight),
+            SettingMessage::MirrorAxisChanged(MirrorAxis::TopBottom),
+            SettingMessage::ScalingChanged(Scaling::Decibel),
+            SettingMessage::BeatReactionChanged(BeatReaction::Flash),
+            SettingMessage::SettingsLayoutChanged(SettingsLayout::Window),
+            SettingMessage::AudioDeviceChanged(AudioDevice::Input(3)),
+            SettingMessage::Mirroring(false),
+            SettingMessage::Loudness(true),
+            SettingMessage::ClickThrough(true),
+            SettingMessage::ResetAll,
+        ];
+        for message in messages {
+            let line = encode(&message).unwrap();
+            assert_eq!(format!("{:?}", decode(&line)), format!("{:?}", Some(message)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn every_slider() {
+        for field in Field::ALL.iter() {
+            let line = encode(&field.message(12.25)).unwrap();
+            assert_eq!(format!("{:?}", decode(&line)), format!("{:?}", Some(field.message(12.25))));
+        }
+    }
+
+    #[test]
+    fn local_messages_stay_local() {
+        assert!(encode(&SettingMessage::InputChanged(Field::Volume, String::from("8"))).is_none());
+        assert!(encode(&SettingMessage::UpdateAudioDevices).is_none());
+        for line in ["", "set volume", "set volume NaN", "set gain 1", "theme solarized", "mirroring maybe", "device speaker 0"].iter() {
+            assert!(decode(line).is_none(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Instant;
+
+// interpolates between the last two spectra so bars move smoothly
+// even if the frame rate and the data rate differ
+pub struct Interpolator {
+    pub enabled: bool,
+    previous: Vec<f32>,
+    current: Vec<f32>,
+    received: Instant,
+    interval: f32, // estimated seconds between two spectra
+}
+
+impl Interpolator {
+    pub fn push(&mut self, data: Vec<f32>) {
+        self.push_at(data, Instant::now())
+    }
+
+    // `push` for a spectrum received at `now`
+    pub fn push_at(&mut self, data: Vec<f32>, now: Instant) {
+        let elapsed = now.duration_since(self.received).as_secs_f32();
+
+        // start from what is currently displayed so bars do not jump
+        self.previous = self.sample_at(now);
+        self.current = data;
+        self.received = now;
+
+        // slowly follow the actual data rate, ignoring huge gaps e.g. after the window was hidden
+        if elapsed < 1.0 {
+            self.interval += (elapsed - self.interval) * 0.2;
+        }
+    }
+
+    pub fn interval(&self) -> f32 {
+        self.interval
+    }
+
+    pub fn sample(&self) -> Vec<f32> {
+        self.sample_at(Instant::now())
+    }
+
+    // the bars as they are displayed at `now`
+    pub fn sample_at(&self, now: Instant) -> Vec<f32> {
+        if !self.enabled || self.previous.len() != self.current.len() {
+            return self.current.clone();
+        }
+
+        let elapsed = now.saturating_duration_since(self.received).as_secs_f32();
+        let t = (elapsed / self.interval.max(0.001)).clamp(0.0, 1.0);
+
+        self.previous.iter()
+            .zip(self.current.iter())
+            // weighted, so t = 1 gives exactly the new spectrum
+            .map(|(p, c)| p * (1.0 - t) + c * t)
+            .collect()
+    }
+}
+
+impl Default for Interpolator {
+    fn default() -> Self {
+        Interpolator {
+            enabled: true,
+            previous: Vec::new(),
+            current: Vec::new(),
+            received: Instant::now(),
+            interval: 1.0 / 60.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn ms(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    // two spectra 20 ms apart, the first one shown right away
+    fn two_spectra(previous: Vec<f32>, current: Vec<f32>) -> (Interpolator, Instant) {
+        let mut interpolator = Interpolator { interval: 0.02, ..Default::default() };
+        let start = interpolator.received;
+        interpolator.push_at(previous, start);
+        interpolator.push_at(current, ms(start, 20));
+        (interpolator, ms(start, 20))
+    }
+
+    #[test]
+    fn starts_at_the_shown_bars_and_ends_at_the_new_ones() {
+        let (interpolator, received) = two_spectra(vec![0.0, 0.5, 1.0], vec![1.0, 0.5, 0.0]);
+        assert_eq!(interpolator.sample_at(received), vec![0.0, 0.5, 1.0]);
+        assert_eq!(interpolator.sample_at(ms(received, 100)), vec![1.0, 0.5, 0.0]);
+
+        // unchanged spectra stay exactly the same in between
+        let (interpolator, received) = two_spectra(vec![0.25, 0.75], vec![0.25, 0.75]);
+        for t in 0..30 {
+            assert_eq!(interpolator.sample_at(ms(received, t)), vec![0.25, 0.75]);
+        }
+    }
+
+    #[test]
+    fn moves_monotonically_between_spectra() {
+        let (interpolator, received) = two_spectra(vec![0.0, 1.0], vec![1.0, 0.2]);
+        let mut last = interpolator.sample_at(received);
+        for t in 1..40 {
+            let bars = interpolator.sample_at(ms(received, t));
+            assert!(bars[0] >= last[0] && bars[0] <= 1.0, "{:?} after {:?}", bars, last);
+            assert!(bars[1] <= last[1] && bars[1] >= 0.2, "{:?} after {:?}", bars, last);
+            last = bars;
+        }
+    }
+
+    #[test]
+    fn disabled_shows_the_newest_spectrum() {
+        let (mut interpolator, received) = two_spectra(vec![0.0], vec![1.0]);
+        interpolator.enabled = false;
+        assert_eq!(interpolator.sample_at(received), vec![1.0]);
+    }
+
+    #[test]
+    fn empty_single_and_changing_bar_counts() {
+        let mut interpolator = Interpolator::default();
+        let start = interpolator.received;
+        assert!(interpolator.sample_at(start).is_empty());
+        for (i, count) in [0, 1, 1, 5, 0, 2].iter().enumerate() {
+            interpolator.push_at(vec![0.5; *count], ms(start, 10 * i as u64));
+            assert_eq!(interpolator.sample_at(ms(start, 10 * i as u64 + 5)).len(), *count);
+        }
+    }
+}
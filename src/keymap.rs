@@ -0,0 +1,207 @@
+use std::fs;
+use iced::keyboard::{KeyCode, Modifiers};
+use colored::*;
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ToggleHelp,
+    ToggleSettings,
+    ToggleFullscreen,
+    CycleTheme,
+    CycleMode,
+    ToggleMirroring,
+    VolumeUp,
+    VolumeDown,
+    BarCountUp,
+    BarCountDown,
+    NextDevice,
+    PreviousDevice,
+    Screenshot,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::ToggleHelp,
+        Action::ToggleSettings,
+        Action::ToggleFullscreen,
+        Action::CycleTheme,
+        Action::CycleMode,
+        Action::ToggleMirroring,
+        Action::VolumeUp,
+        Action::VolumeDown,
+        Action::BarCountUp,
+        Action::BarCountDown,
+        Action::NextDevice,
+        Action::PreviousDevice,
+        Action::Screenshot,
+    ];
+
+    // name used in the keymap file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleSettings => "toggle_settings",
+            Action::ToggleFullscreen => "toggle_fullscreen",
+            Action::CycleTheme => "cycle_theme",
+            Action::CycleMode => "cycle_mode",
+            Action::ToggleMirroring => "toggle_mirroring",
+            Action::VolumeUp => "volume_up",
+            Action::VolumeDown => "volume_down",
+            Action::BarCountUp => "bar_count_up",
+            Action::BarCountDown => "bar_count_down",
+            Action::NextDevice => "next_device",
+            Action::PreviousDevice => "previous_device",
+            Action::Screenshot => "screenshot",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key_code: KeyCode,
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Binding {
+    fn new(key_code: KeyCode) -> Self {
+        Binding { key_code, control: false, shift: false, alt: false }
+    }
+
+    // parses bindings like `F11`, `Up` or `Ctrl+Shift+S`
+    fn parse(s: &str) -> Option<Self> {
+        let mut binding = Binding::new(KeyCode::Escape);
+        let mut key = None;
+        for part in s.split('+').map(|p| p.trim()) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => binding.control = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => key = Some(parse_key(part)?),
+            }
+        }
+        binding.key_code = key?;
+        Some(binding)
+    }
+
+    fn describe(&self) -> String {
+        let mut s = String::new();
+        if self.control { s.push_str("Ctrl+"); }
+        if self.shift { s.push_str("Shift+"); }
+        if self.alt { s.push_str("Alt+"); }
+        s.push_str(&format!("{:?}", self.key_code));
+        s
+    }
+}
+
+pub struct Keymap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = vec![
+            (Binding::new(KeyCode::F1), Action::ToggleHelp),
+            (Binding::new(KeyCode::F10), Action::ToggleSettings),
+            (Binding::new(KeyCode::F11), Action::ToggleFullscreen),
+            (Binding::new(KeyCode::T), Action::CycleTheme),
+            (Binding::new(KeyCode::V), Action::CycleMode),
+            (Binding::new(KeyCode::M), Action::ToggleMirroring),
+            (Binding::new(KeyCode::Up), Action::VolumeUp),
+            (Binding::new(KeyCode::Down), Action::VolumeDown),
+            (Binding::new(KeyCode::Right), Action::BarCountUp),
+            (Binding::new(KeyCode::Left), Action::BarCountDown),
+            (Binding::new(KeyCode::N), Action::NextDevice),
+            (Binding::new(KeyCode::P), Action::PreviousDevice),
+            (Binding::new(KeyCode::F12), Action::Screenshot),
+        ];
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    // loads `keymap` next to the config file, every action listed there
+    // replaces its default binding, e.g. `volume_up = Ctrl+Up`
+    pub fn load() -> Self {
+        let mut keymap = Keymap::default();
+        let path = match config::dir() {
+            Some(d) => d.join("keymap"),
+            None => return keymap,
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => return keymap,
+        };
+
+        for (name, value) in config::parse(&content) {
+            let action = match Action::ALL.iter().find(|a| a.name() == name) {
+                Some(a) => *a,
+                None => {
+                    println!("unknown action in keymap: {}", name.red().bold());
+                    continue;
+                }
+            };
+            let binding = match Binding::parse(&value) {
+                Some(b) => b,
+                None => {
+                    println!("invalid key in keymap: {}", value.red().bold());
+                    continue;
+                }
+            };
+            keymap.bindings.retain(|(_, a)| *a != action);
+            keymap.bindings.push((binding, action));
+        }
+
+        keymap
+    }
+
+    pub fn action(&self, key_code: KeyCode, modifiers: Modifiers) -> Option<Action> {
+        let pressed = Binding {
+            key_code,
+            control: modifiers.control(),
+            shift: modifiers.shift(),
+            alt: modifiers.alt(),
+        };
+        self.bindings.iter()
+            .find(|(b, _)| *b == pressed)
+            .map(|(_, a)| *a)
+    }
+
+    // one line per binding, shown by the help overlay
+    pub fn help_lines(&self) -> Vec<String> {
+        Action::ALL.iter()
+            .filter_map(|action| {
+                let (binding, _) = self.bindings.iter().find(|(_, a)| a == action)?;
+                Some(format!("{:<14} {}", binding.describe(), action.name()))
+            })
+            .collect()
+    }
+}
+
+fn parse_key(s: &str) -> Option<KeyCode> {
+    let key = match s.to_lowercase().as_str() {
+        "a" => KeyCode::A, "b" => KeyCode::B, "c" => KeyCode::C, "d" => KeyCode::D,
+        "e" => KeyCode::E, "f" => KeyCode::F, "g" => KeyCode::G, "h" => KeyCode::H,
+        "i" => KeyCode::I, "j" => KeyCode::J, "k" => KeyCode::K, "l" => KeyCode::L,
+        "m" => KeyCode::M, "n" => KeyCode::N, "o" => KeyCode::O, "p" => KeyCode::P,
+        "q" => KeyCode::Q, "r" => KeyCode::R, "s" => KeyCode::S, "t" => KeyCode::T,
+        "u" => KeyCode::U, "v" => KeyCode::V, "w" => KeyCode::W, "x" => KeyCode::X,
+        "y" => KeyCode::Y, "z" => KeyCode::Z,
+        "0" => KeyCode::Key0, "1" => KeyCode::Key1, "2" => KeyCode::Key2, "3" => KeyCode::Key3,
+        "4" => KeyCode::Key4, "5" => KeyCode::Key5, "6" => KeyCode::Key6, "7" => KeyCode::Key7,
+        "8" => KeyCode::Key8, "9" => KeyCode::Key9,
+        "f1" => KeyCode::F1, "f2" => KeyCode::F2, "f3" => KeyCode::F3, "f4" => KeyCode::F4,
+        "f5" => KeyCode::F5, "f6" => KeyCode::F6, "f7" => KeyCode::F7, "f8" => KeyCode::F8,
+        "f9" => KeyCode::F9, "f10" => KeyCode::F10, "f11" => KeyCode::F11, "f12" => KeyCode::F12,
+        "up" => KeyCode::Up, "down" => KeyCode::Down, "left" => KeyCode::Left, "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp, "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home, "end" => KeyCode::End,
+        "space" => KeyCode::Space, "tab" => KeyCode::Tab, "enter" => KeyCode::Enter,
+        "escape" | "esc" => KeyCode::Escape,
+        "plus" => KeyCode::Plus, "minus" => KeyCode::Minus,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,20 @@
+// capture and analysis pipeline of audiolizer without any ui,
+// the iced frontend in main.rs is only one consumer of it
+pub mod audio;
+pub mod spectrum;
+pub mod agc;
+pub mod smoothing;
+pub mod interpolation;
+pub mod stream;
+pub mod osc;
+pub mod beat;
+pub mod meter;
+pub mod loudness;
+pub mod pitch;
+#[cfg(feature = "midi")]
+pub mod midi;
+mod analyzer;
+
+pub use analyzer::Analyzer;
+pub use audio::{AudioDevice, Samples};
+pub use spectrum::SpectrumFrame;
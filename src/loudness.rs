@@ -0,0 +1,379 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use crate::audio::Samples;
+
+// loudness is measured in 100 ms steps, momentary spans 4 of them, short-term 30
+const MOMENTARY_BLOCKS: usize = 4;
+const SHORT_TERM_BLOCKS: usize = 30;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+// gating blocks are counted in 0.1 dB steps from the absolute gate up to +30 LUFS
+const HISTOGRAM_STEP: f64 = 0.1;
+const HISTOGRAM_BINS: usize = 1000;
+
+// 4x oversampling for the true peak, 12 taps per phase
+const OVERSAMPLING: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+// direct form 1 biquad in f64, the 38 Hz highpass needs the precision
+#[derive(Default, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+// the two stages of the K-weighting filter of ITU-R BS.1770 for any sample rate
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    // high shelf
+    let f0 = 1681.974450955533;
+    let gain = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    // highpass
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    [shelf, highpass]
+}
+
+// weight of every channel in the sum, 5.1 in the order L R C LFE Ls Rs
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6, 3) => 0.0,
+        (6, 4) | (6, 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+// windowed sinc interpolation filter, split into one set of taps per phase
+fn oversampling_filter() -> Vec<[f64; TAPS_PER_PHASE]> {
+    let length = OVERSAMPLING * TAPS_PER_PHASE;
+    // the center sits on a tap, so the phases interpolate at 0, 1/4, 1/2 and 3/4 samples
+    // and phase 0 passes the original samples through, the tap that would make the
+    // filter symmetric lies on a zero of the sinc and is left out
+    let center = (length / 2) as f64;
+    let mut phases = vec![[0.0; TAPS_PER_PHASE]; OVERSAMPLING];
+    for i in 0..length {
+        let t = (i as f64 - center) / OVERSAMPLING as f64;
+        let sinc = if t == 0.0 { 1.0 } else { (std::f64::consts::PI * t).sin() / (std::f64::consts::PI * t) };
+        // hann window
+        let window = 0.5 + 0.5 * (std::f64::consts::PI * (i as f64 - center) / center).cos();
+        phases[i % OVERSAMPLING][i / OVERSAMPLING] = sinc * window;
+    }
+    phases
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+// EBU R128 loudness: momentary, short-term, integrated and true peak
+pub struct Loudness {
+    receiver: Option<mpsc::Receiver<Samples>>,
+    sample_rate: usize,
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    block_sums: Vec<f64>,      // squared weighted samples per channel of the running 100 ms block
+    block_length: usize,
+    block_position: usize,
+    blocks: VecDeque<f64>,     // mean power of the last 100 ms blocks
+    histogram: Vec<(u64, f64)>, // count and summed power of the gating blocks per loudness step
+    gated_count: u64,          // gating blocks above the absolute gate since the last reset
+    gated_power: f64,          // and their summed power
+    integrated: Option<f64>,   // updated with every gating block
+    oversampling: Vec<[f64; TAPS_PER_PHASE]>,
+    peak_history: Vec<VecDeque<f64>>, // last input samples per channel for the interpolation
+    true_peak: f64,
+}
+
+impl Loudness {
+    // `receiver` usually comes from `Analyzer::tap`
+    pub fn new(receiver: mpsc::Receiver<Samples>) -> Self {
+        let mut loudness = Loudness::detached();
+        loudness.receiver = Some(receiver);
+        loudness
+    }
+
+    // without a sample source, samples have to be passed to `process`
+    pub fn detached() -> Self {
+        Loudness {
+            receiver: None,
+            sample_rate: 0,
+            channels: 0,
+            filters: Vec::new(),
+            block_sums: Vec::new(),
+            block_length: 0,
+            block_position: 0,
+            blocks: VecDeque::new(),
+            histogram: vec![(0, 0.0); HISTOGRAM_BINS],
+            gated_count: 0,
+            gated_power: 0.0,
+            integrated: None,
+            oversampling: oversampling_filter(),
+            peak_history: Vec::new(),
+            true_peak: 0.0,
+        }
+    }
+
+    // processes everything that arrived since the last call
+    pub fn update(&mut self) {
+        let samples: Vec<Samples> = match &self.receiver {
+            Some(r) => r.try_iter().collect(),
+            None => return,
+        };
+        for s in samples {
+            self.process(&s);
+        }
+    }
+
+    // starts a new integrated measurement and clears the true peak
+    pub fn reset(&mut self) {
+        self.blocks.clear();
+        self.histogram.iter_mut().for_each(|bin| *bin = (0, 0.0));
+        self.gated_count = 0;
+        self.gated_power = 0.0;
+        self.integrated = None;
+        self.true_peak = 0.0;
+        self.block_position = 0;
+        self.block_sums.iter_mut().for_each(|s| *s = 0.0);
+    }
+
+    pub fn process(&mut self, samples: &Samples) {
+        if samples.channels == 0 || samples.sample_rate == 0 {
+            return;
+        }
+        // another device, everything measured so far is meaningless
+        if samples.sample_rate != self.sample_rate || samples.channels != self.channels {
+            self.sample_rate = samples.sample_rate;
+            self.channels = samples.channels;
+            self.filters = vec![k_weighting(samples.sample_rate as f64); samples.channels];
+            self.block_sums = vec![0.0; samples.channels];
+            self.block_length = samples.sample_rate / 10;
+            self.peak_history = vec![VecDeque::from(vec![0.0; TAPS_PER_PHASE]); samples.channels];
+            self.reset();
+        }
+
+        for frame in samples.data.chunks_exact(self.channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                let sample = *sample as f64;
+
+                let [shelf, highpass] = &mut self.filters[channel];
+                let weighted = highpass.process(shelf.process(sample));
+                self.block_sums[channel] += weighted * weighted;
+
+                let history = &mut self.peak_history[channel];
+                history.pop_back();
+                history.push_front(sample);
+                for phase in self.oversampling.iter() {
+                    let interpolated: f64 = phase.iter().zip(history.iter()).map(|(t, s)| t * s).sum();
+                    self.true_peak = self.true_peak.max(interpolated.abs());
+                }
+                self.true_peak = self.true_peak.max(sample.abs());
+            }
+
+            self.block_position += 1;
+            if self.block_position >= self.block_length {
+                self.finish_block();
+            }
+        }
+    }
+
+    fn finish_block(&mut self) {
+        let length = self.block_length as f64;
+        let channels = self.channels;
+        let power: f64 = self.block_sums.iter()
+            .enumerate()
+            .map(|(channel, sum)| channel_weight(channel, channels) * sum / length)
+            .sum();
+        self.block_sums.iter_mut().for_each(|s| *s = 0.0);
+        self.block_position = 0;
+
+        self.blocks.push_back(power);
+        if self.blocks.len() > SHORT_TERM_BLOCKS {
+            self.blocks.pop_front();
+        }
+
+        // gating blocks overlap by 75 %, so every 100 ms one ends
+        if let Some(power) = self.mean_power(MOMENTARY_BLOCKS) {
+            self.add_gating_block(power);
+        }
+    }
+
+    fn add_gating_block(&mut self, power: f64) {
+        let lufs = power_to_lufs(power);
+        if lufs <= ABSOLUTE_GATE {
+            return;
+        }
+        let bin = (((lufs - ABSOLUTE_GATE) / HISTOGRAM_STEP) as usize).min(HISTOGRAM_BINS - 1);
+        self.histogram[bin].0 += 1;
+        self.histogram[bin].1 += power;
+        self.gated_count += 1;
+        self.gated_power += power;
+
+        // blocks are summed per step, only the step the relative gate falls into is
+        // decided by its center instead of the exact loudness of every block
+        let relative_gate = power_to_lufs(self.gated_power / self.gated_count as f64) + RELATIVE_GATE;
+        let first = ((relative_gate - ABSOLUTE_GATE) / HISTOGRAM_STEP - 0.5).ceil().max(0.0) as usize;
+        let (count, power) = self.histogram[first.min(HISTOGRAM_BINS)..].iter()
+            .fold((0, 0.0), |(count, power), bin| (count + bin.0, power + bin.1));
+        self.integrated = if count > 0 { Some(power_to_lufs(power / count as f64)) } else { None };
+    }
+
+    fn mean_power(&self, count: usize) -> Option<f64> {
+        if self.blocks.len() < count {
+            return None;
+        }
+        Some(self.blocks.iter().rev().take(count).sum::<f64>() / count as f64)
+    }
+
+    // LUFS over the last 400 ms
+    pub fn momentary(&self) -> Option<f32> {
+        self.mean_power(MOMENTARY_BLOCKS).map(|p| power_to_lufs(p) as f32)
+    }
+
+    // LUFS over the last 3 s
+    pub fn short_term(&self) -> Option<f32> {
+        self.mean_power(SHORT_TERM_BLOCKS).map(|p| power_to_lufs(p) as f32)
+    }
+
+    // gated LUFS since the last reset
+    pub fn integrated(&self) -> Option<f32> {
+        self.integrated.map(|lufs| lufs as f32)
+    }
+
+    // highest inter-sample peak since the last reset in dBTP
+    pub fn true_peak(&self) -> Option<f32> {
+        if self.true_peak <= 0.0 {
+            return None;
+        }
+        Some((20.0 * self.true_peak.log10()) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    // stereo sine with `level` dBFS peak amplitude
+    fn sine(freq: f64, level: f64, seconds: f64) -> Vec<f32> {
+        let amplitude = 10f64.powf(level / 20.0);
+        let count = (seconds * SAMPLE_RATE as f64) as usize;
+        (0..count)
+            .flat_map(|i| {
+                let t = i as f64 / SAMPLE_RATE as f64;
+                let v = (amplitude * (2.0 * std::f64::consts::PI * freq * t).sin()) as f32;
+                vec![v, v]
+            })
+            .collect()
+    }
+
+    // in blocks of 512 frames like an audio callback would
+    fn feed(loudness: &mut Loudness, data: &[f32]) {
+        for block in data.chunks(1024) {
+            loudness.process(&Samples {
+                data: block.to_vec(),
+                channels: 2,
+                sample_rate: SAMPLE_RATE,
+            });
+        }
+    }
+
+    fn assert_near(value: Option<f32>, expected: f32, below: f32, above: f32) {
+        let value = value.expect("no reading");
+        assert!(
+            value >= expected - below && value <= expected + above,
+            "{} is not within {} -{} +{}", value, expected, below, above,
+        );
+    }
+
+    // EBU Tech 3341 case 1 and 2: a stereo 1 kHz sine reads its level
+    #[test]
+    fn sine_reads_its_level() {
+        for level in &[-23.0, -33.0] {
+            let mut loudness = Loudness::detached();
+            feed(&mut loudness, &sine(1000.0, *level, 20.0));
+            assert_near(loudness.momentary(), *level as f32, 0.1, 0.1);
+            assert_near(loudness.short_term(), *level as f32, 0.1, 0.1);
+            assert_near(loudness.integrated(), *level as f32, 0.1, 0.1);
+        }
+    }
+
+    // EBU Tech 3341 case 3: the quiet parts fall below the relative gate
+    #[test]
+    fn relative_gate() {
+        let mut loudness = Loudness::detached();
+        feed(&mut loudness, &sine(1000.0, -36.0, 10.0));
+        feed(&mut loudness, &sine(1000.0, -23.0, 60.0));
+        feed(&mut loudness, &sine(1000.0, -36.0, 10.0));
+        assert_near(loudness.integrated(), -23.0, 0.1, 0.1);
+    }
+
+    #[test]
+    fn absolute_gate() {
+        let mut loudness = Loudness::detached();
+        feed(&mut loudness, &sine(1000.0, -80.0, 5.0));
+        assert!(loudness.momentary().is_some());
+        assert!(loudness.integrated().is_none());
+    }
+
+    #[test]
+    fn reset_clears_the_integrated_loudness() {
+        let mut loudness = Loudness::detached();
+        feed(&mut loudness, &sine(1000.0, -23.0, 5.0));
+        loudness.reset();
+        assert!(loudness.integrated().is_none());
+        assert!(loudness.true_peak().is_none());
+    }
+
+    // EBU Tech 3341 case 15: a 576 Hz sine at -6 dBFS reads -6.0 dBTP, +0.2 / -0.4
+    #[test]
+    fn true_peak() {
+        let mut loudness = Loudness::detached();
+        feed(&mut loudness, &sine(576.0, -6.0, 2.0));
+        assert_near(loudness.true_peak(), -6.0, 0.4, 0.2);
+    }
+
+    // phase 0 of the interpolation passes the samples through unchanged
+    #[test]
+    fn oversampling_keeps_the_samples() {
+        let phases = oversampling_filter();
+        for (tap, value) in phases[0].iter().enumerate() {
+            let expected = if tap == TAPS_PER_PHASE / 2 { 1.0 } else { 0.0 };
+            assert!((value - expected).abs() < 1e-12, "tap {} is {}", tap, value);
+        }
+    }
+}
@@ -1,23 +1,61 @@
 use iced::{
     executor, time, Application, Command, Container, Element, Length, alignment,
     Subscription, button, Text, Alignment,
-    window::icon,
+    window::{self, icon}, keyboard, Color,
 };
-use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use colored::*;
 
 
 use audioviz;
-mod audio;
-use audio::*;
+use audiolizer::{audio, spectrum, Analyzer, AudioDevice};
+use audiolizer::agc::Agc;
+use audiolizer::smoothing::Smoother;
+use audiolizer::interpolation::Interpolator;
+use audiolizer::stream::StreamServer;
+use audiolizer::osc::OscSender;
+use audiolizer::beat::BeatDetector;
+use audiolizer::meter::Meter;
+use audiolizer::loudness::Loudness;
+use audiolizer::pitch::PitchDetector;
+#[cfg(feature = "midi")]
+use audiolizer::midi::{self, MidiBand, MidiSender};
 mod theme;
+mod bench;
+mod stats;
+use stats::Stats;
+mod config;
+use config::SettingsLayout;
+mod keymap;
+mod remote;
+use remote::RemoteCommand;
+mod detached;
+use detached::SettingsWindow;
+mod click_through;
+use keymap::{Action, Keymap};
 
 mod ui;
 use ui::bars::*;
-use ui::settings::{SettingMessage, Settings};
+use ui::meters::{LoudnessPanel, Meters};
+use ui::settings::{Field, SettingMessage, Settings};
 
 use gag::Gag;
 
+// width of the settings panel when it is placed next to the bars
+const SETTINGS_PANEL_WIDTH: u16 = 560;
+// but the panel never takes more than this share of a narrow window
+const SETTINGS_PANEL_SHARE: f32 = 0.5;
+
 pub fn main() -> iced::Result {
+    if std::env::args().any(|a| a == "--bench-draw") {
+        bench::run();
+        return Ok(());
+    }
+    if std::env::args().any(|a| a == detached::CHILD_FLAG) {
+        return detached::run();
+    }
+
     // dont print any alsa or jack errors on *nix systems to stderr
     let _print_gag = Gag::stderr().unwrap();
 
@@ -26,60 +64,312 @@ pub fn main() -> iced::Result {
     let rgba: Vec<u8> = image.as_raw().to_vec();
 
     let icon = icon::Icon::from_rgba(rgba, 1024, 1024).unwrap();
+
+    let mut config = config::Config::load();
+    config.apply_args(std::env::args().skip(1));
+    #[cfg(not(feature = "midi"))]
+    if config.midi {
+        println!("{} audiolizer was built without the {} feature", "--midi is ignored:".yellow().bold(), "midi".bold());
+    }
     
     Visual::run(iced::Settings {
         antialiasing: true,
         window: iced::window::Settings {
             icon: Some(icon),
             min_size: Some((500, 0)),
+            decorations: !config.borderless,
+            transparent: config.borderless || config.transparent,
+            always_on_top: config.always_on_top,
             ..iced::window::Settings::default()
         },
+        flags: config,
         ..iced::Settings::default()
     })
 }
 
+#[cfg(feature = "midi")]
+fn connect_midi(config: &config::Config) -> Option<MidiSender> {
+    if !config.midi {
+        return None;
+    }
+
+    let mut bands = Vec::new();
+    for band in config.midi_bands.split(',').filter(|b| !b.trim().is_empty()) {
+        match MidiBand::parse(band) {
+            Some(b) => bands.push(b),
+            None => println!("invalid midi band: {}", band.trim().red().bold()),
+        }
+    }
+    if bands.is_empty() {
+        bands = midi::default_bands();
+    }
+
+    match MidiSender::connect(&config.midi_port, bands.clone()) {
+        Ok(m) => {
+            let bands: Vec<String> = bands.iter().map(MidiBand::describe).collect();
+            println!("sending midi to {}: {}", config.midi_port.green().bold(), bands.join(", "));
+            Some(m)
+        }
+        Err(e) => {
+            println!("could not open midi port {}: {}", config.midi_port.yellow(), e);
+            println!("available ports: {}", MidiSender::ports().join(", "));
+            None
+        }
+    }
+}
+
 struct Visual {
     theme: theme::Theme,
     bars: Bars,
+    agc: Agc,
+    smoother: Smoother,
+    interpolator: Interpolator,
     settings: Settings,
-    event_sender: mpsc::Sender<audioviz::Event>,
+    analyzer: Analyzer,
+    stream: Option<StreamServer>,
+    osc: Option<OscSender>,
+    #[cfg(feature = "midi")]
+    midi: Option<MidiSender>,
+    beat: BeatDetector,
+    show_bpm: bool,
+    meter: Meter,
+    meters: Meters,
+    show_meters: bool,
+    loudness: Loudness,
+    loudness_panel: LoudnessPanel,
+    show_loudness: bool,
+    pitch: PitchDetector,
+    show_pitch: bool,
+    bar_ranges: Arc<Vec<(f32, f32)>>, // of the newest spectrum, to find the bar of the pitch
+    stats: Stats,
+    config: config::Config,        // with the command line flags applied
+    stored_config: config::Config, // as it is saved, without them
+    keymap: Keymap,
+    show_help: bool,
     toggle_button_state: button::State,
     show_sliders: bool,
+    click_through: bool, // as it was last applied to the window
+    click_through_retry: Instant,
+    click_through_failed: bool, // the error is only printed once
+    settings_window: Option<SettingsWindow>, // the settings in a window of their own
+    window_width: u32, // logical pixels, limits the width of the side panel
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SliderMessage(SettingMessage),
     Update,
+    Frame,
     ToggleSliders,
+    ToggleFullscreen,
+    KeyPressed(keyboard::KeyCode, keyboard::Modifiers),
+    Remote(remote::Request),
+    ResetLoudness,
+    Resized(u32),
+    SettingsWindow(Option<SettingMessage>),
+}
+
+impl Visual {
+    fn shortcut(&mut self, action: Action) -> Command<Message> {
+        match action {
+            Action::ToggleHelp => self.show_help = !self.show_help,
+            Action::ToggleSettings => return self.update(Message::ToggleSliders),
+            Action::ToggleFullscreen => return self.update(Message::ToggleFullscreen),
+            Action::Screenshot => self.screenshot(),
+            _ => {
+                for msg in self.settings.shortcut(action) {
+                    self.update(Message::SliderMessage(msg));
+                }
+            }
+        }
+        Command::none()
+    }
+
+    // changes the running and the stored config, settings given on the
+    // command line are only saved once they are changed in the ui
+    fn store(&mut self, change: impl Fn(&mut config::Config)) {
+        change(&mut self.config);
+        change(&mut self.stored_config);
+        self.stored_config.save();
+    }
+
+    fn settings_window_wanted(&self) -> bool {
+        self.show_sliders && self.config.settings_layout == SettingsLayout::Window
+    }
+
+    // opens or closes the settings window after the panel was toggled or moved
+    fn sync_settings_window(&mut self) {
+        if !self.settings_window_wanted() {
+            self.settings_window = None;
+        } else if self.settings_window.is_none() {
+            match SettingsWindow::open(&self.settings.state()) {
+                Ok(w) => self.settings_window = Some(w),
+                Err(e) => println!("could not open the settings window: {}", e.to_string().red()),
+            }
+        }
+    }
+
+    // answer to every remote control request
+    fn remote_state(&mut self) -> String {
+        let mut values: Vec<String> = Field::ALL.iter()
+            .map(|f| format!("\"{}\":{}", f.name(), self.settings.value(*f)))
+            .collect();
+
+        let device = match self.settings.audio_device() {
+            AudioDevice::Output(i) => format!("{{\"output\":{}}}", i),
+            AudioDevice::Input(i) => format!("{{\"input\":{}}}", i),
+        };
+        values.push(format!("\"theme\":\"{}\"", self.theme.name()));
+        values.push(format!("\"mirroring\":{}", self.bars.mirroring));
+        values.push(format!("\"device\":{}", device));
+
+        format!("{{{}}}", values.join(","))
+    }
+
+    fn screenshot(&self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = format!("audiolizer-{}.png", timestamp);
+
+        match self.bars.screenshot().save(&path) {
+            Ok(_) => println!("saved screenshot to {}", path.green().bold()),
+            Err(e) => println!("could not save screenshot: {}", e.to_string().red()),
+        }
+    }
+
+    fn overlay_lines(&self, frame_interval: Duration) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.show_bpm {
+            match self.beat.bpm() {
+                Some(bpm) => lines.push(format!("bpm: {:.0}", bpm)),
+                None => lines.push(String::from("bpm: --")),
+            }
+        }
+        if self.show_pitch {
+            match self.pitch.pitch() {
+                Some(p) => lines.push(format!("pitch: {} {:+.0} cents ({:.1} Hz)", p.note, p.cents, p.frequency)),
+                None => lines.push(String::from("pitch: --")),
+            }
+        }
+        if self.stats.enabled {
+            lines.extend(self.performance_lines(frame_interval));
+        }
+        if self.show_help {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(self.keymap.help_lines());
+        }
+        lines
+    }
+
+    fn performance_lines(&self, frame_interval: Duration) -> Vec<String> {
+        let (callback_frames, sample_rate) = audio::callback_info();
+        let callback_ms = if sample_rate > 0 {
+            callback_frames as f32 / sample_rate as f32 * 1000.0
+        } else {
+            0.0
+        };
+
+        // capture buffer + time between spectra + time until the next frame shows them
+        let latency_ms = callback_ms
+            + self.interpolator.interval() * 1000.0
+            + frame_interval.as_secs_f32() * 1000.0;
+
+        vec![
+            format!("fps: {:.1} / {:.0}", self.stats.fps(), self.settings.frame_rate()),
+            format!("update: {:.3} ms", self.stats.update_time.as_secs_f64() * 1000.0),
+            format!("draw: {:.3} ms", self.bars.draw_time.get().as_secs_f64() * 1000.0),
+            format!("audio buffer: {} frames ({:.1} ms)", callback_frames, callback_ms),
+            format!("latency: ~{:.1} ms", latency_ms),
+            format!("late frames: {}", self.stats.late_frames),
+        ]
+    }
 }
 
 impl Application for Visual{
     type Executor = executor::Default;
     type Message = Message;
-    type Flags = ();
+    type Flags = config::Config;
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
+    fn new(flags: config::Config) -> (Self, Command<Message>) {
         let audio_device = AudioDevice::Output(0);
         let config = audioviz::Config {
             ..Default::default()
         };
-        let audio_stream = audioviz::AudioStream::init(
-            config.clone(),
-        );
-        let event_sender = audio_stream.get_event_sender();
-        let (device_tx, device_rx) = mpsc::channel();
-        init_audio_sender(event_sender.clone(), audio_device, device_rx);
+        let analyzer = Analyzer::new(config.clone(), audio_device);
+
+        let stream = if flags.stream {
+            match StreamServer::start(flags.stream_port, flags.stream_rate, flags.stream_format) {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    println!("could not start spectrum stream on port {}: {}", flags.stream_port.to_string().yellow(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let osc = if flags.osc {
+            match OscSender::new(&flags.osc_target) {
+                Ok(o) => {
+                    println!("sending osc to {}", flags.osc_target.green().bold());
+                    Some(o)
+                }
+                Err(e) => {
+                    println!("could not send osc to {}: {}", flags.osc_target.yellow(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut meter = Meter::new(analyzer.tap());
+        meter.crossover_low = flags.crossover_low;
+        meter.crossover_high = flags.crossover_high;
         
         (
             Visual {
                 theme: Default::default(),
                 //bars: Bars {data: Vec::new(), ..Default::default()},
                 bars: Default::default(),
-                settings: Settings::new(event_sender.clone(), theme::Theme::default(), config, device_tx),
-                event_sender,
+                agc: Default::default(),
+                smoother: Default::default(),
+                interpolator: Default::default(),
+                settings: Settings::new(analyzer.event_sender(), theme::Theme::default(), config, analyzer.device_sender(), &flags),
+                stream,
+                osc,
+                #[cfg(feature = "midi")]
+                midi: connect_midi(&flags),
+                beat: Default::default(),
+                show_bpm: false,
+                meter,
+                meters: Default::default(),
+                show_meters: false,
+                loudness: Loudness::new(analyzer.tap()),
+                loudness_panel: LoudnessPanel::new(),
+                show_loudness: false,
+                pitch: PitchDetector::new(analyzer.tap()),
+                show_pitch: false,
+                bar_ranges: Arc::new(Vec::new()),
+                analyzer,
+                stats: Default::default(),
+                config: flags,
+                stored_config: config::Config::load(),
+                keymap: Keymap::load(),
+                show_help: false,
                 toggle_button_state: button::State::new(),
                 show_sliders: false,
+                click_through: false,
+                click_through_retry: Instant::now(),
+                click_through_failed: false,
+                settings_window: None,
+                // iced opens windows at 1024 x 768
+                window_width: 1024,
             },
             Command::none(),
         )
@@ -92,22 +382,164 @@ impl Application for Visual{
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::Update => {
-                self.bars.cache.clear();
-                let (tx, rx) = mpsc::channel();
-                self.event_sender.send(audioviz::Event::RequestData(tx)).unwrap();
-                self.bars.data = rx.recv().unwrap();
+                let start = Instant::now();
+                // drained even when hidden, so the samples don't pile up
+                self.meter.update();
+                self.loudness.update();
+                self.pitch.update(self.show_pitch);
+                if let Some(frame) = self.analyzer.latest() {
+                    self.bar_ranges = frame.ranges;
+                    let mut data = frame.bars;
+                    self.agc.process(&mut data);
+                    self.settings.agc_gain = self.agc.gain();
+                    let beat = self.beat.process(&data);
+                    self.smoother.process(&mut data);
+                    if let Some(osc) = &self.osc {
+                        osc.send_bars(&data);
+                        osc.send_rms(audio::rms());
+                        if beat {
+                            osc.send_beat(self.beat.bpm().unwrap_or(0.0));
+                        }
+                    }
+                    #[cfg(feature = "midi")]
+                    if let Some(midi) = &mut self.midi {
+                        midi.send(&data);
+                    }
+                    if let Some(stream) = &mut self.stream {
+                        stream.publish(&data, self.settings.audio_config());
+                    }
+                    self.interpolator.push(data);
+                }
+                // only the analysis counts, other messages would overwrite it with their time
+                self.stats.update_time = start.elapsed();
+            },
+            Message::Frame => {
+                // nothing changed, keep the cached geometry
+                let data = self.interpolator.sample();
+                if data != self.bars.data {
+                    self.bars.data = data;
+                    self.bars.cache.clear();
+                }
+                // flashing changes the color of the cached bars
+                let pulse = self.beat.pulse();
+                if self.bars.beat_reaction == BeatReaction::Flash && (pulse > 0.0 || self.bars.pulse > 0.0) {
+                    self.bars.cache.clear();
+                }
+                self.bars.pulse = pulse;
 
-                if self.bars.mirroring {
-                    for i in 0..self.bars.data.len() {
-                        self.bars.data.insert(0, self.bars.data[i * 2]);
+                let highlight = match (self.show_pitch, self.pitch.pitch()) {
+                    (true, Some(pitch)) => spectrum::bar_for_frequency(&self.bar_ranges, pitch.frequency),
+                    _ => None,
+                };
+                if highlight != self.bars.highlight {
+                    self.bars.highlight = highlight;
+                    self.bars.cache.clear();
+                }
+
+                if self.show_meters {
+                    self.meters.channels = self.meter.channels();
+                    self.meters.bands = self.meter.bands();
+                    self.meters.r = self.bars.r;
+                    self.meters.g = self.bars.g;
+                    self.meters.b = self.bars.b;
+                }
+
+                // the window only exists once iced runs and may not be mapped
+                // on the first frames, so this is retried every second until it works
+                if self.click_through != self.config.click_through && Instant::now() >= self.click_through_retry {
+                    match click_through::set(&self.title(), self.config.click_through) {
+                        Ok(()) => {
+                            self.click_through = self.config.click_through;
+                            self.click_through_failed = false;
+                        }
+                        Err(e) => {
+                            if !self.click_through_failed {
+                                println!("could not change click-through: {}", e.yellow());
+                                self.click_through_failed = true;
+                            }
+                            self.click_through_retry = Instant::now() + Duration::from_secs(1);
+                        }
                     }
                 }
+
+                let frame_interval = Duration::from_millis(1_000 / self.settings.frame_rate() as u64);
+                self.stats.frame(frame_interval);
+                self.bars.overlay = self.overlay_lines(frame_interval);
+            },
+            Message::Remote(request) => {
+                let command = match request.command.clone() {
+                    RemoteCommand::Get => Command::none(),
+                    RemoteCommand::Setting(messages) => {
+                        for msg in messages {
+                            self.update(Message::SliderMessage(msg));
+                        }
+                        Command::none()
+                    }
+                    RemoteCommand::Action(action) => self.shortcut(action),
+                };
+                request.reply(self.remote_state());
+                return command;
+            },
+            Message::ResetLoudness => {
+                self.loudness.reset();
+            },
+            Message::Resized(width) => {
+                self.window_width = width;
             },
             Message::ToggleSliders => {
                 self.show_sliders = !self.show_sliders;
+                self.sync_settings_window();
+            },
+            Message::SettingsWindow(Some(msg)) => {
+                // the window already shows what was changed in it
+                let window = self.settings_window.take();
+                let command = self.update(Message::SliderMessage(msg));
+                if self.settings_window_wanted() && self.settings_window.is_none() {
+                    self.settings_window = window;
+                }
+                return command;
+            },
+            Message::SettingsWindow(None) => {
+                // closed by the user
+                self.settings_window = None;
+                self.show_sliders = false;
+            },
+            Message::KeyPressed(key_code, modifiers) => {
+                if let Some(action) = self.keymap.action(key_code, modifiers) {
+                    return self.shortcut(action);
+                }
+            },
+            Message::ToggleFullscreen => {
+                let fullscreen = !self.config.fullscreen;
+                if let Some(window) = &mut self.settings_window {
+                    window.send(&SettingMessage::Fullscreen(fullscreen));
+                }
+                self.settings.update(SettingMessage::Fullscreen(fullscreen));
+                self.store(|c| c.fullscreen = fullscreen);
             },
             Message::SliderMessage(msg) => {
+                // shortcuts and the remote control change settings too
+                if let Some(window) = &mut self.settings_window {
+                    window.send(&msg);
+                }
+                // most settings change how bars are drawn
+                self.bars.cache.clear();
                 match msg {
+                    SettingMessage::InputSubmitted(field) => {
+                        if let Some(msg) = self.settings.submit(field) {
+                            return self.update(Message::SliderMessage(msg));
+                        }
+                    }
+                    SettingMessage::Reset(field) => {
+                        let msg = self.settings.reset(field);
+                        return self.update(Message::SliderMessage(msg));
+                    }
+                    SettingMessage::ResetAll => {
+                        for msg in self.settings.reset_all() {
+                            self.update(Message::SliderMessage(msg));
+                        }
+                        self.settings.update(msg);
+                    }
                     SettingMessage::ThemeChanged(t) => {
                         self.theme = t;
                         self.settings.update(msg)
@@ -116,10 +548,126 @@ impl Application for Visual{
                         self.bars.mirroring = v;
                         self.settings.update(msg)
                     }
+                    SettingMessage::MirrorAxisChanged(a) => {
+                        self.bars.mirror_axis = a;
+                        self.settings.update(msg)
+                    }
                     SettingMessage::BarWidthChanged(w) => {
                         self.bars.width = w;
                         self.settings.update(msg);
                     }
+                    SettingMessage::AutoGain(v) => {
+                        self.agc.enabled = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::AgcAttack(a) => {
+                        self.agc.attack = a * 0.001;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::AgcRelease(r) => {
+                        self.agc.release = r * 0.001;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::BarAttack(a) => {
+                        self.smoother.attack = a * 0.001;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::BarRelease(r) => {
+                        self.smoother.release = r * 0.001;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::MaxFreq(_) => {
+                        self.settings.update(msg);
+                        self.analyzer.set_max_frequency(self.settings.audio_config().max_frequency);
+                    }
+                    SettingMessage::BarRefreshRate(r) => {
+                        self.analyzer.set_refresh_rate(r as usize);
+                        self.beat.set_refresh_rate(r);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::BeatReactionChanged(r) => {
+                        self.bars.beat_reaction = r;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Meters(v) => {
+                        self.show_meters = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Loudness(v) => {
+                        self.show_loudness = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::CrossoverLow(f) => {
+                        self.meter.crossover_low = f;
+                        self.store(|c| c.crossover_low = f);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::CrossoverHigh(f) => {
+                        self.meter.crossover_high = f;
+                        self.store(|c| c.crossover_high = f);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::ShowPitch(v) => {
+                        self.show_pitch = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::ShowBpm(v) => {
+                        self.show_bpm = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::PerformanceOverlay(v) => {
+                        self.stats.enabled = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Fullscreen(v) => {
+                        self.store(|c| c.fullscreen = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Borderless(v) => {
+                        self.store(|c| c.borderless = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::AlwaysOnTop(v) => {
+                        self.store(|c| c.always_on_top = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::HideSettingsButton(v) => {
+                        self.store(|c| c.hide_settings_button = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Transparent(v) => {
+                        self.store(|c| c.transparent = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::ClickThrough(v) => {
+                        self.store(|c| c.click_through = v);
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::SettingsLayoutChanged(l) => {
+                        self.store(|c| c.settings_layout = l);
+                        self.settings.update(msg);
+                        self.sync_settings_window();
+                    }
+                    SettingMessage::BarOpacity(o) => {
+                        self.bars.alpha = o * 0.01;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::Interpolation(v) => {
+                        self.interpolator.enabled = v;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::ScalingChanged(s) => {
+                        self.bars.scaling = s;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::DbFloor(f) => {
+                        self.bars.db_floor = f;
+                        self.settings.update(msg);
+                    }
+                    SettingMessage::DbCeiling(c) => {
+                        self.bars.db_ceiling = c;
+                        self.settings.update(msg);
+                    }
                     _ => {
                         self.settings.update(msg)
                     }
@@ -131,13 +679,76 @@ impl Application for Visual{
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        time::every(std::time::Duration::from_millis(1_000 / self.settings.bar_rr_sv as u64))
-            .map(|_| Message::Update)
+        let remote = if self.config.remote {
+            remote::listen(self.config.remote_port).map(Message::Remote)
+        } else {
+            Subscription::none()
+        };
+
+        let settings_window = match &self.settings_window {
+            Some(window) => window.subscription().map(Message::SettingsWindow),
+            None => Subscription::none(),
+        };
+
+        Subscription::batch(vec![
+            remote,
+            settings_window,
+            time::every(std::time::Duration::from_millis(1_000 / self.settings.bar_refresh_rate() as u64))
+                .map(|_| Message::Update),
+            time::every(std::time::Duration::from_millis(1_000 / self.settings.frame_rate() as u64))
+                .map(|_| Message::Frame),
+            iced_native::subscription::events_with(|event, status| {
+                // ignore keys that a widget already handled
+                if status == iced_native::event::Status::Captured {
+                    return None;
+                }
+                match event {
+                    iced_native::Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) => {
+                        Some(Message::KeyPressed(key_code, modifiers))
+                    }
+                    iced_native::Event::Window(iced_native::window::Event::Resized { width, .. }) => {
+                        Some(Message::Resized(width))
+                    }
+                    _ => None,
+                }
+            }),
+        ])
+    }
+
+    fn background_color(&self) -> Color {
+        if self.config.transparent {
+            Color::TRANSPARENT
+        } else {
+            Color::WHITE
+        }
+    }
+
+    fn mode(&self) -> window::Mode {
+        if self.config.fullscreen {
+            window::Mode::Fullscreen
+        } else {
+            window::Mode::Windowed
+        }
     }
 
 
     fn view(&mut self) -> Element<Message> { 
-        let canvas = self.bars.view();
+        let canvas: Element<Message> = if self.show_meters || self.show_loudness {
+            let mut row = iced::Row::new()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .spacing(5)
+                .push(self.bars.view());
+            if self.show_meters {
+                row = row.push(self.meters.view());
+            }
+            if self.show_loudness {
+                row = row.push(self.loudness_panel.view(&self.loudness, self.theme));
+            }
+            row.into()
+        } else {
+            self.bars.view()
+        };
         
         let slider_toggle = button::Button::new(
             &mut self.toggle_button_state,
@@ -146,38 +757,76 @@ impl Application for Visual{
         .on_press(Message::ToggleSliders)
         .style(self.theme);
         
-        let bars: Element<Message> = Container::new(canvas)
+        let bars = Container::new(canvas)
             .width(Length::Fill)
             .height(Length::Fill)
             .padding(2)
             .center_x()
-            .center_y()
-            .style(self.theme)
-            .into();
+            .center_y();
+
+        // only the bars are visible in transparent mode
+        let bars: Element<Message> = if self.config.transparent {
+            bars.style(theme::TransparentContainer).into()
+        } else {
+            bars.style(self.theme).into()
+        };
 
         let mut content = iced::Column::new()
             .align_items(Alignment::End)
             .height(Length::Fill)
             .spacing(2)
-            .padding(2)
-            .push(slider_toggle)
-            .push(bars);
+            .padding(2);
 
-        if self.show_sliders {
-            content = content.push(
-                self.settings.view()
-                    .map(Message::SliderMessage)
-            );
+        // settings stay reachable with F10 when the button is hidden
+        if !self.config.hide_settings_button {
+            content = content.push(slider_toggle);
+        }
+
+        match (self.show_sliders, self.config.settings_layout) {
+            (false, _) | (true, SettingsLayout::Window) => {
+                content = content.push(bars);
+            }
+            (true, SettingsLayout::Bottom) => {
+                content = content
+                    .push(bars)
+                    .push(
+                        self.settings.view()
+                            .map(Message::SliderMessage)
+                    );
+            }
+            (true, SettingsLayout::Side) => {
+                // the bars keep the full height, only the width is shared
+                let width = (SETTINGS_PANEL_WIDTH as f32).min(self.window_width as f32 * SETTINGS_PANEL_SHARE);
+                let panel = Container::new(
+                        self.settings.view()
+                            .map(Message::SliderMessage)
+                    )
+                    .width(Length::Units(width as u16))
+                    .height(Length::Fill);
+
+                content = content.push(
+                    iced::Row::new()
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .spacing(2)
+                        .push(bars)
+                        .push(panel)
+                );
+            }
         }
         
         
-        Container::new(content)
+        let content = Container::new(content)
             .width(Length::Fill)
             .height(Length::Fill)
             .center_x()
-            .center_y()
-            .style(self.theme)
-            .into()
+            .center_y();
+
+        if self.config.transparent {
+            content.style(theme::TransparentContainer).into()
+        } else {
+            content.style(self.theme).into()
+        }
 
     }
 }
@@ -0,0 +1,268 @@
+use std::sync::mpsc;
+use crate::audio::Samples;
+
+// vu integration time
+const RMS_TIME: f32 = 0.3;
+// peaks fall by 20 dB in this many seconds, like a ppm
+const PEAK_FALL_TIME: f32 = 1.7;
+const PEAK_HOLD_TIME: f32 = 2.0;
+// quieter than this is shown as silence
+pub const FLOOR_DB: f32 = -90.0;
+
+// levels of one channel, all in dBFS
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelLevel {
+    pub rms: f32,
+    pub peak: f32,
+    pub hold: f32, // highest peak of the last `PEAK_HOLD_TIME` seconds
+}
+
+impl Default for ChannelLevel {
+    fn default() -> Self {
+        ChannelLevel {
+            rms: FLOOR_DB,
+            peak: FLOOR_DB,
+            hold: FLOOR_DB,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct ChannelState {
+    mean_square: f32,
+    peak: f32,
+    hold: f32,
+    hold_age: usize, // samples since `hold` was set
+}
+
+// second order butterworth section, coefficients from the audio eq cookbook
+#[derive(Default, Clone, Copy)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn lowpass(freq: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Biquad::prepare(freq, sample_rate);
+        Biquad::normalized((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0, cos, alpha)
+    }
+
+    fn highpass(freq: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Biquad::prepare(freq, sample_rate);
+        Biquad::normalized((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0, cos, alpha)
+    }
+
+    fn prepare(freq: f32, sample_rate: f32) -> (f32, f32) {
+        // keep the corner below nyquist, otherwise the filter explodes
+        let freq = freq.max(1.0).min(sample_rate * 0.45);
+        let w0 = 2.0 * std::f32::consts::PI * freq / sample_rate;
+        let q = std::f32::consts::FRAC_1_SQRT_2;
+        (w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    fn normalized(b0: f32, b1: f32, b2: f32, cos: f32, alpha: f32) -> Self {
+        let a0 = 1.0 + alpha;
+        Biquad {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            ..Default::default()
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+// stereo vu / peak meter and bass, mid and treble energy of the raw samples
+pub struct Meter {
+    pub crossover_low: f32,  // Hz between bass and mids
+    pub crossover_high: f32, // Hz between mids and treble
+    receiver: mpsc::Receiver<Samples>,
+    channels: [ChannelState; 2],
+    bass: Biquad,
+    mid_high: Biquad,
+    mid_low: Biquad,
+    treble: Biquad,
+    bands: [f32; 3], // mean squares
+    filters_for: (f32, f32, usize), // crossovers and sample rate the filters were built for
+}
+
+impl Meter {
+    // `receiver` usually comes from `Analyzer::tap`
+    pub fn new(receiver: mpsc::Receiver<Samples>) -> Self {
+        Meter {
+            crossover_low: 250.0,
+            crossover_high: 4000.0,
+            receiver,
+            channels: [ChannelState::default(); 2],
+            bass: Biquad::default(),
+            mid_high: Biquad::default(),
+            mid_low: Biquad::default(),
+            treble: Biquad::default(),
+            bands: [0.0; 3],
+            filters_for: (0.0, 0.0, 0),
+        }
+    }
+
+    // processes everything that arrived since the last call
+    pub fn update(&mut self) {
+        while let Ok(samples) = self.receiver.try_recv() {
+            self.process(&samples);
+        }
+    }
+
+    pub fn process(&mut self, samples: &Samples) {
+        if samples.channels == 0 || samples.sample_rate == 0 {
+            return;
+        }
+        let sample_rate = samples.sample_rate as f32;
+        if self.filters_for != (self.crossover_low, self.crossover_high, samples.sample_rate) {
+            self.bass = Biquad::lowpass(self.crossover_low, sample_rate);
+            self.mid_high = Biquad::highpass(self.crossover_low, sample_rate);
+            self.mid_low = Biquad::lowpass(self.crossover_high, sample_rate);
+            self.treble = Biquad::highpass(self.crossover_high, sample_rate);
+            self.filters_for = (self.crossover_low, self.crossover_high, samples.sample_rate);
+        }
+
+        let rms_coefficient = 1.0 - (-1.0 / (RMS_TIME * sample_rate)).exp();
+        let peak_fall = 0.1f32.powf(1.0 / (PEAK_FALL_TIME * sample_rate));
+        let hold_samples = (PEAK_HOLD_TIME * sample_rate) as usize;
+
+        for frame in samples.data.chunks(samples.channels) {
+            // mono sources show up on both sides
+            let left = frame[0];
+            let right = if frame.len() > 1 { frame[1] } else { frame[0] };
+
+            for (state, sample) in self.channels.iter_mut().zip([left, right].iter()) {
+                state.mean_square += (sample * sample - state.mean_square) * rms_coefficient;
+
+                let magnitude = sample.abs();
+                state.peak = if magnitude > state.peak { magnitude } else { state.peak * peak_fall };
+
+                state.hold_age += 1;
+                if state.peak >= state.hold || state.hold_age > hold_samples {
+                    state.hold = state.peak;
+                    state.hold_age = 0;
+                }
+            }
+
+            let mono = (left + right) * 0.5;
+            let bass = self.bass.process(mono);
+            let mid = self.mid_low.process(self.mid_high.process(mono));
+            let treble = self.treble.process(mono);
+            for (energy, sample) in self.bands.iter_mut().zip([bass, mid, treble].iter()) {
+                *energy += (sample * sample - *energy) * rms_coefficient;
+            }
+        }
+    }
+
+    // left and right
+    pub fn channels(&self) -> [ChannelLevel; 2] {
+        let level = |state: &ChannelState| ChannelLevel {
+            rms: power_to_db(state.mean_square),
+            peak: amplitude_to_db(state.peak),
+            hold: amplitude_to_db(state.hold),
+        };
+        [level(&self.channels[0]), level(&self.channels[1])]
+    }
+
+    // rms of bass, mids and treble in dBFS
+    pub fn bands(&self) -> [f32; 3] {
+        [
+            power_to_db(self.bands[0]),
+            power_to_db(self.bands[1]),
+            power_to_db(self.bands[2]),
+        ]
+    }
+}
+
+fn amplitude_to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.log10()).max(FLOOR_DB)
+}
+
+fn power_to_db(power: f32) -> f32 {
+    (10.0 * power.log10()).max(FLOOR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn detached() -> Meter {
+        let (_sender, receiver) = mpsc::channel();
+        Meter::new(receiver)
+    }
+
+    // stereo sine with peak amplitude `amplitude`
+    fn sine(frequency: f32, amplitude: f32, seconds: f32) -> Samples {
+        let count = (seconds * SAMPLE_RATE as f32) as usize;
+        Samples {
+            data: (0..count)
+                .flat_map(|i| {
+                    let t = i as f32 / SAMPLE_RATE as f32;
+                    let v = amplitude * (2.0 * std::f32::consts::PI * frequency * t).sin();
+                    vec![v, v]
+                })
+                .collect(),
+            channels: 2,
+            sample_rate: SAMPLE_RATE,
+        }
+    }
+
+    #[test]
+    fn full_scale_sine() {
+        let mut meter = detached();
+        meter.process(&sine(1000.0, 1.0, 2.0));
+        for level in meter.channels().iter() {
+            assert!((level.rms + 3.01).abs() < 0.1, "rms is {}", level.rms);
+            assert!(level.peak.abs() < 0.1, "peak is {}", level.peak);
+            assert!(level.hold.abs() < 0.1, "hold is {}", level.hold);
+        }
+    }
+
+    #[test]
+    fn peak_hold_is_released() {
+        let mut meter = detached();
+        meter.process(&sine(1000.0, 1.0, 0.1));
+        meter.process(&sine(1000.0, 0.0, PEAK_HOLD_TIME * 0.9));
+        let [left, _] = meter.channels();
+        assert!(left.hold.abs() < 0.1, "hold fell to {} too early", left.hold);
+        assert!(left.peak < -10.0, "peak is still {}", left.peak);
+
+        meter.process(&sine(1000.0, 0.0, PEAK_HOLD_TIME * 0.2));
+        let [left, _] = meter.channels();
+        assert!(left.hold < -10.0, "hold is still {}", left.hold);
+    }
+
+    #[test]
+    fn bands() {
+        let mut meter = detached();
+        meter.process(&sine(100.0, 0.5, 1.0));
+        let [bass, mid, treble] = meter.bands();
+        assert!(bass > mid + 10.0 && bass > treble + 10.0, "100 Hz reads {} {} {}", bass, mid, treble);
+
+        let mut meter = detached();
+        meter.process(&sine(10_000.0, 0.5, 1.0));
+        let [bass, mid, treble] = meter.bands();
+        assert!(treble > mid + 10.0 && treble > bass + 10.0, "10 kHz reads {} {} {}", bass, mid, treble);
+    }
+}
@@ -0,0 +1,172 @@
+use midir::{MidiOutput, MidiOutputConnection};
+
+// part of the spectrum that is sent as one control change
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiBand {
+    pub low: f32,  // start as fraction of the bars, 0.0 is the first bar
+    pub high: f32, // end as fraction of the bars, 1.0 is the last bar
+    pub channel: u8,    // 1 - 16
+    pub controller: u8, // 0 - 119
+}
+
+impl MidiBand {
+    // `low-high:channel:controller`, e.g. `0-0.1:1:20`
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().split(':');
+        let mut range = parts.next()?.split('-');
+        let band = MidiBand {
+            low: range.next()?.trim().parse().ok()?,
+            high: range.next()?.trim().parse().ok()?,
+            channel: parts.next()?.trim().parse().ok()?,
+            controller: parts.next()?.trim().parse().ok()?,
+        };
+        let valid = band.low < band.high
+            && (1..=16).contains(&band.channel)
+            && band.controller <= 119;
+        if valid { Some(band) } else { None }
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{}-{}:{}:{}", self.low, self.high, self.channel, self.controller)
+    }
+
+    // mean of the bars in this band, scaled to 0 - 127
+    fn value(&self, bars: &[f32]) -> u8 {
+        let start = ((self.low * bars.len() as f32) as usize).min(bars.len());
+        let end = ((self.high * bars.len() as f32).ceil() as usize).max(start + 1).min(bars.len());
+        let bars = &bars[start..end];
+        if bars.is_empty() {
+            return 0;
+        }
+        let mean = bars.iter().sum::<f32>() / bars.len() as f32;
+        (mean.max(0.0).min(1.0) * 127.0).round() as u8
+    }
+}
+
+// bass, mids and highs on controllers 20 - 22 of channel 1
+pub fn default_bands() -> Vec<MidiBand> {
+    vec![
+        MidiBand { low: 0.0, high: 0.1, channel: 1, controller: 20 },
+        MidiBand { low: 0.1, high: 0.5, channel: 1, controller: 21 },
+        MidiBand { low: 0.5, high: 1.0, channel: 1, controller: 22 },
+    ]
+}
+
+pub struct MidiSender {
+    connection: MidiOutputConnection,
+    bands: Vec<MidiBand>,
+    last: Vec<Option<u8>>, // last sent value per band, unchanged values are not sent again
+}
+
+impl MidiSender {
+    // connects to the first output port whose name contains `port`,
+    // `virtual` creates an ALSA port named audiolizer that other programs can connect to
+    pub fn connect(port: &str, bands: Vec<MidiBand>) -> Result<Self, String> {
+        let output = MidiOutput::new("audiolizer").map_err(|e| e.to_string())?;
+
+        let connection = if port == "virtual" {
+            create_virtual(output)?
+        } else {
+            let ports = output.ports();
+            let found = ports.iter()
+                .find(|p| output.port_name(p).map(|n| n.contains(port)).unwrap_or(false))
+                .ok_or_else(|| format!("no midi output port matches '{}'", port))?
+                .clone();
+            output.connect(&found, "audiolizer").map_err(|e| e.to_string())?
+        };
+
+        Ok(MidiSender {
+            connection,
+            last: vec![None; bands.len()],
+            bands,
+        })
+    }
+
+    // names of all available output ports
+    pub fn ports() -> Vec<String> {
+        match MidiOutput::new("audiolizer") {
+            Ok(output) => output.ports().iter().filter_map(|p| output.port_name(p).ok()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub fn send(&mut self, bars: &[f32]) {
+        for (band, last) in self.bands.iter().zip(self.last.iter_mut()) {
+            let value = band.value(bars);
+            if *last == Some(value) {
+                continue;
+            }
+            *last = Some(value);
+            let status = 0xB0 | (band.channel - 1);
+            let _ = self.connection.send(&[status, band.controller, value]);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn create_virtual(output: MidiOutput) -> Result<MidiOutputConnection, String> {
+    use midir::os::unix::VirtualOutput;
+    output.create_virtual("audiolizer").map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn create_virtual(_output: MidiOutput) -> Result<MidiOutputConnection, String> {
+    Err(String::from("virtual midi ports are only available on linux and macos"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let cases = [
+            ("0-0.1:1:20", Some((0.0, 0.1, 1, 20))),
+            (" 0.5 - 1 : 16 : 119 ", Some((0.5, 1.0, 16, 119))),
+            ("0-1:1:0", Some((0.0, 1.0, 1, 0))),
+            // channels are 1 - 16, controllers above 119 are channel mode messages
+            ("0-1:0:20", None),
+            ("0-1:17:20", None),
+            ("0-1:1:120", None),
+            // empty or reversed range
+            ("0.5-0.5:1:20", None),
+            ("0.6-0.2:1:20", None),
+            ("0-0.1:1", None),
+            ("0:1:20", None),
+            ("low-high:1:20", None),
+            ("", None),
+        ];
+        for (input, expected) in cases.iter() {
+            let expected = expected.map(|(low, high, channel, controller)| MidiBand { low, high, channel, controller });
+            assert_eq!(MidiBand::parse(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn describe_parses_back() {
+        for band in default_bands() {
+            assert_eq!(MidiBand::parse(&band.describe()), Some(band));
+        }
+    }
+
+    #[test]
+    fn value() {
+        let bars = [1.0, 0.5, 0.0, 0.0, 2.0, -1.0, 0.25, 0.25, 0.25, 0.25];
+        let band = |low, high| MidiBand { low, high, channel: 1, controller: 20 };
+        let cases = [
+            (band(0.0, 0.1), 127),
+            (band(0.0, 0.2), 95), // mean 0.75
+            (band(0.2, 0.4), 0),
+            (band(0.4, 0.5), 127), // clipped at 1.0
+            (band(0.5, 0.6), 0),   // and at 0.0
+            (band(0.6, 1.0), 32),
+            // narrower than a bar still covers one
+            (band(0.0, 0.01), 127),
+            (band(0.95, 1.0), 32),
+        ];
+        for (band, expected) in cases.iter() {
+            assert_eq!(band.value(&bars), *expected, "{}", band.describe());
+        }
+        assert_eq!(band(0.0, 1.0).value(&[]), 0);
+    }
+}
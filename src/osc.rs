@@ -0,0 +1,132 @@
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+
+// sends Open Sound Control messages over udp, all addresses start with /audiolizer
+pub struct OscSender {
+    socket: UdpSocket,
+}
+
+impl OscSender {
+    // `target` is `host:port` of the receiving application
+    pub fn new(target: &str) -> io::Result<Self> {
+        let target = target.to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address for the osc target"))?;
+        // the local socket has to be of the same family as the target
+        let local = match target {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(target)?;
+        // a missing receiver must never block the caller
+        socket.set_nonblocking(true)?;
+
+        Ok(OscSender {
+            socket,
+        })
+    }
+
+    // level of every bar, one float argument per bar
+    pub fn send_bars(&self, bars: &[f32]) {
+        self.send("/audiolizer/bars", bars);
+    }
+
+    // rms of the raw samples
+    pub fn send_rms(&self, rms: f32) {
+        self.send("/audiolizer/rms", &[rms]);
+    }
+
+    // sent on every detected beat with the estimated bpm, 0.0 while it is unknown
+    pub fn send_beat(&self, bpm: f32) {
+        self.send("/audiolizer/beat", &[bpm]);
+    }
+
+    fn send(&self, address: &str, args: &[f32]) {
+        // nobody listening shows up as a connection refused error, which is fine
+        let _ = self.socket.send(&message(address, args));
+    }
+}
+
+// encodes an osc message with float32 arguments
+pub fn message(address: &str, args: &[f32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(address.len() + args.len() * 5 + 8);
+    push_string(&mut message, address);
+
+    let mut tags = String::from(",");
+    tags.extend(args.iter().map(|_| 'f'));
+    push_string(&mut message, &tags);
+
+    for arg in args {
+        message.extend_from_slice(&arg.to_be_bytes());
+    }
+    message
+}
+
+// osc strings are null terminated and padded to a multiple of 4 bytes
+fn push_string(message: &mut Vec<u8>, s: &str) {
+    message.extend_from_slice(s.as_bytes());
+    let padding = 4 - s.len() % 4;
+    message.extend(std::iter::repeat(0).take(padding));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn receive(receiver: &UdpSocket) -> Vec<u8> {
+        receiver.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        let mut buffer = [0; 1024];
+        let length = receiver.recv(&mut buffer).unwrap();
+        buffer[..length].to_vec()
+    }
+
+    #[test]
+    fn encoding() {
+        // 15 characters get one null, 16 get four
+        assert_eq!(message("/audiolizer/rms", &[0.5]), [
+            b"/audiolizer/rms\0".as_ref(),
+            b",f\0\0",
+            &0.5f32.to_be_bytes(),
+        ].concat());
+        assert_eq!(message("/audiolizer/beat", &[]), [
+            b"/audiolizer/beat\0\0\0\0".as_ref(),
+            b",\0\0\0",
+        ].concat());
+        assert_eq!(message("/audiolizer/bars", &[1.0, -2.0, 0.25]), [
+            b"/audiolizer/bars\0\0\0\0".as_ref(),
+            b",fff\0\0\0\0",
+            &1.0f32.to_be_bytes(),
+            &(-2.0f32).to_be_bytes(),
+            &0.25f32.to_be_bytes(),
+        ].concat());
+    }
+
+    #[test]
+    fn sends_over_udp() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender = OscSender::new(&receiver.local_addr().unwrap().to_string()).unwrap();
+
+        sender.send_bars(&[0.5, 1.0]);
+        assert_eq!(receive(&receiver), message("/audiolizer/bars", &[0.5, 1.0]));
+        sender.send_beat(120.0);
+        let received = receive(&receiver);
+        assert_eq!(received.len(), 20 + 4 + 4);
+        assert_eq!(&received[..20], b"/audiolizer/beat\0\0\0\0");
+        assert_eq!(&received[20..24], b",f\0\0");
+        assert_eq!(&received[24..], &120.0f32.to_be_bytes());
+    }
+
+    #[test]
+    fn sends_over_ipv6() {
+        // not every host has ipv6
+        let receiver = match UdpSocket::bind("[::1]:0") {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let sender = OscSender::new(&receiver.local_addr().unwrap().to_string()).unwrap();
+        sender.send_rms(0.5);
+        assert_eq!(receive(&receiver), message("/audiolizer/rms", &[0.5]));
+    }
+}
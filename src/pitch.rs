@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use crate::audio::Samples;
+
+// lowest detectable fundamental, a little below the low E of a bass
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 2000.0;
+// samples compared per lag
+const WINDOW: usize = 1024;
+// yin threshold, lower is stricter
+const THRESHOLD: f32 = 0.15;
+// quieter input is not analyzed, rms in dBFS
+const GATE_DB: f32 = -50.0;
+// analyses per second, yin is too expensive to run on every sample block
+const RATE: f32 = 10.0;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pitch {
+    pub frequency: f32,
+    pub note: String, // e.g. `A4`
+    pub cents: f32,   // -50 to 50 from the equal tempered note, A4 = 440 Hz
+    pub clarity: f32, // 0 to 1, how periodic the signal is
+}
+
+impl Pitch {
+    pub fn from_frequency(frequency: f32, clarity: f32) -> Self {
+        let midi = 69.0 + 12.0 * (frequency / 440.0).log2();
+        let nearest = midi.round();
+        let index = (nearest as i32).rem_euclid(12) as usize;
+        let octave = (nearest as i32).div_euclid(12) - 1;
+
+        Pitch {
+            frequency,
+            note: format!("{}{}", NOTE_NAMES[index], octave),
+            cents: (midi - nearest) * 100.0,
+            clarity,
+        }
+    }
+}
+
+// fundamental frequency of the capture stream with the yin algorithm
+pub struct PitchDetector {
+    receiver: mpsc::Receiver<Samples>,
+    buffer: VecDeque<f32>, // mono, newest sample at the back
+    sample_rate: usize,
+    since_analysis: usize, // samples since the last analysis
+    pitch: Option<Pitch>,
+}
+
+impl PitchDetector {
+    // `receiver` usually comes from `Analyzer::tap`
+    pub fn new(receiver: mpsc::Receiver<Samples>) -> Self {
+        PitchDetector {
+            receiver,
+            buffer: VecDeque::new(),
+            sample_rate: 0,
+            since_analysis: 0,
+            pitch: None,
+        }
+    }
+
+    // processes everything that arrived since the last call, without `analyze`
+    // the samples are only buffered, yin is too expensive to run while nobody looks
+    pub fn update(&mut self, analyze: bool) {
+        while let Ok(samples) = self.receiver.try_recv() {
+            if samples.channels == 0 || samples.sample_rate == 0 {
+                continue;
+            }
+            if samples.sample_rate != self.sample_rate {
+                self.sample_rate = samples.sample_rate;
+                self.buffer.clear();
+            }
+
+            for frame in samples.data.chunks(samples.channels) {
+                self.buffer.push_back(frame.iter().sum::<f32>() / frame.len() as f32);
+            }
+            self.since_analysis += samples.data.len() / samples.channels;
+
+            let length = WINDOW + self.max_lag();
+            while self.buffer.len() > length {
+                self.buffer.pop_front();
+            }
+        }
+
+        if !analyze {
+            self.pitch = None;
+            return;
+        }
+        if self.buffer.len() == WINDOW + self.max_lag() && self.since_analysis as f32 >= self.sample_rate as f32 / RATE {
+            self.since_analysis = 0;
+            self.pitch = self.analyze();
+        }
+    }
+
+    pub fn pitch(&self) -> Option<&Pitch> {
+        self.pitch.as_ref()
+    }
+
+    fn max_lag(&self) -> usize {
+        (self.sample_rate as f32 / MIN_FREQUENCY) as usize
+    }
+
+    fn analyze(&self) -> Option<Pitch> {
+        let samples: Vec<f32> = self.buffer.iter().copied().collect();
+
+        let rms = (samples[..WINDOW].iter().map(|s| s * s).sum::<f32>() / WINDOW as f32).sqrt();
+        if 20.0 * rms.log10() < GATE_DB {
+            return None;
+        }
+
+        let min_lag = (self.sample_rate as f32 / MAX_FREQUENCY) as usize;
+        let max_lag = self.max_lag();
+
+        // cumulative mean normalized difference
+        let mut difference = vec![1.0; max_lag + 1];
+        let mut running_sum = 0.0;
+        for lag in 1..=max_lag {
+            let d: f32 = (0..WINDOW).map(|j| (samples[j] - samples[j + lag]).powi(2)).sum();
+            running_sum += d;
+            difference[lag] = if running_sum > 0.0 { d * lag as f32 / running_sum } else { 1.0 };
+        }
+
+        // first dip below the threshold, followed down to its minimum
+        let mut lag = (min_lag.max(2)..max_lag).find(|l| difference[*l] < THRESHOLD)?;
+        while lag + 1 < max_lag && difference[lag + 1] < difference[lag] {
+            lag += 1;
+        }
+
+        // parabolic interpolation between the neighbouring lags
+        let (a, b, c) = (difference[lag - 1], difference[lag], difference[lag + 1]);
+        let denominator = a - 2.0 * b + c;
+        let offset = if denominator.abs() > f32::EPSILON { 0.5 * (a - c) / denominator } else { 0.0 };
+
+        let frequency = self.sample_rate as f32 / (lag as f32 + offset);
+        Some(Pitch::from_frequency(frequency, (1.0 - b).max(0.0).min(1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    // half a second of mono signal, enough for one analysis
+    fn detect(signal: impl Fn(f32) -> f32, analyze: bool) -> Option<Pitch> {
+        let (sender, receiver) = mpsc::channel();
+        let mut detector = PitchDetector::new(receiver);
+        sender.send(Samples {
+            data: (0..SAMPLE_RATE / 2).map(|i| signal(i as f32 / SAMPLE_RATE as f32)).collect(),
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+        }).unwrap();
+        detector.update(analyze);
+        detector.pitch().cloned()
+    }
+
+    fn sine(frequency: f32, amplitude: f32) -> impl Fn(f32) -> f32 {
+        move |t| amplitude * (2.0 * std::f32::consts::PI * frequency * t).sin()
+    }
+
+    fn sawtooth(frequency: f32, amplitude: f32) -> impl Fn(f32) -> f32 {
+        move |t| amplitude * (2.0 * (t * frequency).fract() - 1.0)
+    }
+
+    #[test]
+    fn note_names() {
+        let cases = [
+            (440.0, "A4", 0.0),
+            (466.16, "A#4", 0.0),
+            (27.5, "A0", 0.0),
+            (261.63, "C4", 0.0),
+            // 49 cents below A4
+            (440.0 * 2f32.powf(-0.49 / 12.0), "A4", -49.0),
+            // 30 cents above B3 is still B3, not C4
+            (246.94 * 2f32.powf(0.3 / 12.0), "B3", 30.0),
+        ];
+        for (frequency, note, cents) in cases.iter() {
+            let pitch = Pitch::from_frequency(*frequency, 1.0);
+            assert_eq!(pitch.note, *note, "{} Hz", frequency);
+            assert!((pitch.cents - cents).abs() < 0.5, "{} Hz is {} cents", frequency, pitch.cents);
+        }
+    }
+
+    #[test]
+    fn sine_frequency() {
+        for frequency in [41.2, 110.0, 440.0, 1318.5].iter() {
+            let pitch = detect(sine(*frequency, 0.5), true).expect("no pitch");
+            assert!((pitch.frequency / frequency - 1.0).abs() < 0.01, "{} Hz read as {}", frequency, pitch.frequency);
+        }
+    }
+
+    #[test]
+    fn sawtooth_frequency() {
+        for frequency in [82.41, 220.0, 659.26].iter() {
+            let pitch = detect(sawtooth(*frequency, 0.5), true).expect("no pitch");
+            assert!((pitch.frequency / frequency - 1.0).abs() < 0.01, "{} Hz read as {}", frequency, pitch.frequency);
+        }
+    }
+
+    #[test]
+    fn quiet_input_is_gated() {
+        let below = 10f32.powf((GATE_DB - 10.0) / 20.0);
+        assert!(detect(sine(440.0, below), true).is_none());
+        let above = 10f32.powf((GATE_DB + 10.0) / 20.0);
+        assert!(detect(sine(440.0, above), true).is_some());
+    }
+
+    #[test]
+    fn not_analyzed_while_hidden() {
+        assert!(detect(sine(440.0, 0.5), false).is_none());
+    }
+}
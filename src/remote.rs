@@ -0,0 +1,289 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use iced::Subscription;
+use iced::futures::channel::mpsc as channel;
+use iced::futures::stream::{BoxStream, StreamExt};
+use colored::*;
+use audiolizer::{Analyzer, AudioDevice};
+use crate::keymap::Action;
+use crate::theme::Theme;
+use crate::ui::settings::{Field, SettingMessage};
+
+// request line and headers together, every valid request is far smaller
+const MAX_REQUEST: u64 = 8 * 1024;
+
+// what a remote client asked for, every request is answered with the current configuration
+#[derive(Debug, Clone)]
+pub enum RemoteCommand {
+    Get,
+    Setting(Vec<SettingMessage>),
+    Action(Action),
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub command: RemoteCommand,
+    reply: mpsc::Sender<String>,
+}
+
+impl Request {
+    pub fn reply(&self, body: String) {
+        let _ = self.reply.send(body);
+    }
+}
+
+// http server on localhost:`port`, its requests arrive as subscription messages
+pub fn listen(port: u16) -> Subscription<Request> {
+    Subscription::from_recipe(Listener { port })
+}
+
+struct Listener {
+    port: u16,
+}
+
+impl<H: std::hash::Hasher, E> iced_native::subscription::Recipe<H, E> for Listener {
+    type Output = Request;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+        self.port.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, E>) -> BoxStream<'static, Request> {
+        let (sender, receiver) = channel::unbounded();
+
+        match TcpListener::bind(("127.0.0.1", self.port)) {
+            Ok(listener) => {
+                println!("remote control listening on {}", format!("http://127.0.0.1:{}", self.port).green().bold());
+                let port = self.port;
+                thread::spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        // a slow or idle client must not hold up the others
+                        let sender = sender.clone();
+                        thread::spawn(move || {
+                            let _ = handle(stream, &sender, port);
+                        });
+                    }
+                });
+            }
+            Err(e) => println!("could not start remote control on port {}: {}", self.port.to_string().yellow(), e),
+        }
+
+        receiver.boxed()
+    }
+}
+
+fn handle(mut stream: TcpStream, sender: &channel::UnboundedSender<Request>, port: u16) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut reader = BufReader::new(stream.try_clone()?).take(MAX_REQUEST);
+    let (request_line, headers) = match read_head(&mut reader)? {
+        Some(head) => head,
+        // the request didn't fit
+        None if reader.limit() == 0 => return respond(&mut stream, "431 Request Header Fields Too Large", "{\"error\":\"request too large\"}"),
+        None => return respond(&mut stream, "400 Bad Request", "{\"error\":\"incomplete request\"}"),
+    };
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+
+    let command = match check_headers(&headers, port).and_then(|_| parse(method, path)) {
+        Ok(c) => c,
+        Err((status, message)) => return respond(&mut stream, status, &format!("{{\"error\":\"{}\"}}", message)),
+    };
+
+    let (reply, response) = mpsc::channel();
+    if sender.unbounded_send(Request { command, reply }).is_err() {
+        return respond(&mut stream, "503 Service Unavailable", "{\"error\":\"audiolizer is shutting down\"}");
+    }
+    match response.recv_timeout(Duration::from_secs(2)) {
+        Ok(body) => respond(&mut stream, "200 OK", &body),
+        Err(_) => respond(&mut stream, "504 Gateway Timeout", "{\"error\":\"no answer from the ui\"}"),
+    }
+}
+
+// the request line and the headers, `None` if the input ends before the empty line after them
+fn read_head(reader: &mut impl BufRead) -> std::io::Result<Option<(String, Vec<(String, String)>)>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // header names are case insensitive
+    let mut headers = Vec::new();
+    let mut line = String::new();
+    loop {
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.trim().is_empty() {
+            return Ok(Some((request_line, headers)));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+        line.clear();
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body,
+    )
+}
+
+// web pages can't be allowed to control audiolizer: browsers send an `Origin` with
+// requests made by scripts and forms, and a `Host` other than 127.0.0.1 or localhost
+// means a foreign domain was made to resolve to 127.0.0.1 (dns rebinding)
+fn check_headers(headers: &[(String, String)], port: u16) -> Result<(), (&'static str, String)> {
+    if headers.iter().any(|(name, _)| name == "origin") {
+        return Err(("403 Forbidden", String::from("requests from web pages are not allowed")));
+    }
+    let allowed = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    let host = headers.iter().find(|(name, _)| name == "host").map(|(_, value)| value);
+    match host {
+        Some(host) if allowed.iter().any(|a| a.eq_ignore_ascii_case(host)) => Ok(()),
+        _ => Err(("403 Forbidden", format!("the host has to be 127.0.0.1:{} or localhost:{}", port, port))),
+    }
+}
+
+// GET  /config
+// POST /set/<field>/<value>       e.g. /set/volume/80, clamped like the text inputs
+// POST /theme/<light|dark|midnight>
+// POST /mirroring/<on|off>
+// POST /device/<output|input>/<index>
+// POST /action/<action>            any action of the keymap, e.g. /action/next_device
+fn parse(method: &str, path: &str) -> Result<RemoteCommand, (&'static str, String)> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let not_found = || ("404 Not Found", format!("unknown path {}", path));
+    let bad_value = |v: &str| ("400 Bad Request", format!("invalid value {}", v));
+
+    match (method, segments.as_slice()) {
+        ("GET", ["config"]) => Ok(RemoteCommand::Get),
+        ("POST", ["set", name, value]) => {
+            let field = Field::ALL.iter().find(|f| f.name() == *name).ok_or_else(not_found)?;
+            // the text inputs would silently ignore NaN and infinity
+            value.parse::<f32>().ok().filter(|v| v.is_finite()).ok_or_else(|| bad_value(value))?;
+            Ok(RemoteCommand::Setting(vec![
+                SettingMessage::InputChanged(*field, value.to_string()),
+                SettingMessage::InputSubmitted(*field),
+            ]))
+        }
+        ("POST", ["theme", name]) => {
+            let theme = Theme::ALL.iter().find(|t| t.name() == *name).ok_or_else(|| bad_value(name))?;
+            Ok(RemoteCommand::Setting(vec![SettingMessage::ThemeChanged(*theme)]))
+        }
+        ("POST", ["mirroring", value]) => match *value {
+            "on" => Ok(RemoteCommand::Setting(vec![SettingMessage::Mirroring(true)])),
+            "off" => Ok(RemoteCommand::Setting(vec![SettingMessage::Mirroring(false)])),
+            _ => Err(bad_value(value)),
+        },
+        ("POST", ["device", kind, index]) => {
+            let index: usize = index.parse().map_err(|_| bad_value(index))?;
+            let device = match *kind {
+                "output" => AudioDevice::Output(index),
+                "input" => AudioDevice::Input(index),
+                _ => return Err(bad_value(kind)),
+            };
+            let (input_devices, output_devices) = Analyzer::devices();
+            let count = match device {
+                AudioDevice::Output(_) => output_devices.len(),
+                AudioDevice::Input(_) => input_devices.len(),
+            };
+            // the audio thread indexes the device list directly
+            if index >= count {
+                return Err(bad_value(&index.to_string()));
+            }
+            Ok(RemoteCommand::Setting(vec![SettingMessage::AudioDeviceChanged(device)]))
+        }
+        ("POST", ["action", name]) => {
+            let action = Action::ALL.iter().find(|a| a.name() == *name).ok_or_else(not_found)?;
+            Ok(RemoteCommand::Action(*action))
+        }
+        ("GET", _) | ("POST", _) => Err(not_found()),
+        _ => Err(("405 Method Not Allowed", format!("unsupported method {}", method))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the command as its debug output or the status of the error
+    fn parsed(method: &str, path: &str) -> Result<String, &'static str> {
+        parse(method, path).map(|c| format!("{:?}", c)).map_err(|(status, _)| status)
+    }
+
+    #[test]
+    fn paths() {
+        let cases: [(&str, &str, Result<&str, &str>); 23] = [
+            ("GET", "/config", Ok("Get")),
+            ("GET", "/config/", Ok("Get")),
+            ("POST", "/set/volume/80", Ok(r#"Setting([InputChanged(Volume, "80"), InputSubmitted(Volume)])"#)),
+            ("POST", "/set/db_floor/-12.5", Ok(r#"Setting([InputChanged(DbFloor, "-12.5"), InputSubmitted(DbFloor)])"#)),
+            ("POST", "/set/volume/loud", Err("400 Bad Request")),
+            ("POST", "/set/volume/NaN", Err("400 Bad Request")),
+            ("POST", "/set/volume/inf", Err("400 Bad Request")),
+            ("POST", "/set/volume/-infinity", Err("400 Bad Request")),
+            ("POST", "/set/gain/1", Err("404 Not Found")),
+            ("POST", "/set/volume", Err("404 Not Found")),
+            ("POST", "/theme/midnight", Ok("Setting([ThemeChanged(Midnight)])")),
+            ("POST", "/theme/solarized", Err("400 Bad Request")),
+            ("POST", "/mirroring/on", Ok("Setting([Mirroring(true)])")),
+            ("POST", "/mirroring/off", Ok("Setting([Mirroring(false)])")),
+            ("POST", "/mirroring/maybe", Err("400 Bad Request")),
+            ("POST", "/device/speaker/0", Err("400 Bad Request")),
+            ("POST", "/device/output/first", Err("400 Bad Request")),
+            ("POST", "/action/next_device", Ok("Action(NextDevice)")),
+            ("POST", "/action/explode", Err("404 Not Found")),
+            ("GET", "/set/volume/80", Err("404 Not Found")),
+            ("POST", "/config", Err("404 Not Found")),
+            ("GET", "/", Err("404 Not Found")),
+            ("DELETE", "/config", Err("405 Method Not Allowed")),
+        ];
+        for (method, path, expected) in cases.iter() {
+            assert_eq!(parsed(method, path), expected.map(String::from), "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn head() {
+        let request = "POST /set/volume/80 HTTP/1.1\r\nHost: localhost:7701\r\nUser-Agent: curl\r\n\r\n";
+        let (request_line, headers) = read_head(&mut request.as_bytes()).unwrap().unwrap();
+        assert_eq!(request_line.trim_end(), "POST /set/volume/80 HTTP/1.1");
+        assert_eq!(headers, vec![
+            (String::from("host"), String::from("localhost:7701")),
+            (String::from("user-agent"), String::from("curl")),
+        ]);
+
+        // cut off by the client or by the size limit
+        assert!(read_head(&mut "GET /config HTTP/1.1\r\nHost: localhost:7701\r\n".as_bytes()).unwrap().is_none());
+        let huge = format!("GET /config HTTP/1.1\r\nHost: localhost:7701\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_REQUEST as usize));
+        let mut reader = huge.as_bytes().take(MAX_REQUEST);
+        assert!(read_head(&mut reader).unwrap().is_none());
+        assert_eq!(reader.limit(), 0);
+    }
+
+    #[test]
+    fn headers() {
+        let cases: [(&[(&str, &str)], bool); 8] = [
+            (&[("host", "127.0.0.1:7701")], true),
+            (&[("host", "localhost:7701"), ("accept", "*/*")], true),
+            (&[("host", "LOCALHOST:7701")], true),
+            (&[("host", "127.0.0.1:7702")], false),
+            (&[("host", "rebound.example:7701")], false),
+            (&[], false),
+            (&[("host", "localhost:7701"), ("origin", "http://rebound.example")], false),
+            (&[("host", "localhost:7701"), ("origin", "null")], false),
+        ];
+        for (headers, allowed) in cases.iter() {
+            let headers: Vec<(String, String)> = headers.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect();
+            assert_eq!(check_headers(&headers, 7701).is_ok(), *allowed, "{:?}", headers);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Instant;
+
+// temporal smoothing of every bar, bars rise with `attack` and fall with `release`
+pub struct Smoother {
+    pub attack: f32,  // in seconds
+    pub release: f32, // in seconds
+    values: Vec<f32>,
+    last_update: Instant,
+}
+
+impl Smoother {
+    pub fn process(&mut self, data: &mut [f32]) {
+        self.process_at(data, Instant::now())
+    }
+
+    // `process` for a spectrum taken at `now`
+    pub fn process_at(&mut self, data: &mut [f32], now: Instant) {
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        // bar count changed, nothing to smooth against
+        if self.values.len() != data.len() {
+            self.values = data.to_vec();
+            return;
+        }
+
+        let attack = coefficient(dt, self.attack);
+        let release = coefficient(dt, self.release);
+
+        for (value, smoothed) in data.iter_mut().zip(self.values.iter_mut()) {
+            let c = if *value > *smoothed { attack } else { release };
+            *smoothed += (*value - *smoothed) * c;
+            *value = *smoothed;
+        }
+    }
+}
+
+// fraction of the distance to the target covered after `dt` seconds,
+// independent of how often process() gets called
+fn coefficient(dt: f32, tau: f32) -> f32 {
+    if tau <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-dt / tau).exp()
+    }
+}
+
+impl Default for Smoother {
+    fn default() -> Self {
+        Smoother {
+            attack: 0.0,
+            release: 0.15,
+            values: Vec::new(),
+            last_update: Instant::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // the first call only takes over the bars, the second one is `dt` later
+    fn step(smoother: &mut Smoother, from: f32, to: f32, dt: f32) -> f32 {
+        let start = smoother.last_update;
+        smoother.values.clear();
+        smoother.process_at(&mut [from], start);
+        let mut data = [to];
+        smoother.process_at(&mut data, start + Duration::from_secs_f32(dt));
+        data[0]
+    }
+
+    #[test]
+    fn rises_with_attack_and_falls_with_release() {
+        let mut smoother = Smoother { attack: 0.1, release: 0.5, ..Default::default() };
+        // one time constant covers 1 - 1/e of the way
+        let covered = 1.0 - (-1.0f32).exp();
+        assert!((step(&mut smoother, 0.0, 1.0, 0.1) - covered).abs() < 1e-4);
+        assert!((step(&mut smoother, 1.0, 0.0, 0.5) - (1.0 - covered)).abs() < 1e-4);
+        // after the attack time a rise is much further along than a fall
+        assert!(step(&mut smoother, 0.0, 1.0, 0.1) > 1.0 - step(&mut smoother, 1.0, 0.0, 0.1));
+    }
+
+    #[test]
+    fn zero_time_constants_are_instant() {
+        let mut smoother = Smoother { attack: 0.0, release: 0.0, ..Default::default() };
+        assert_eq!(step(&mut smoother, 0.0, 1.0, 0.01), 1.0);
+        assert_eq!(step(&mut smoother, 1.0, 0.25, 0.01), 0.25);
+    }
+
+    #[test]
+    fn bar_count_changes() {
+        let mut smoother = Smoother::default();
+        let start = smoother.last_update;
+        for (i, count) in [4, 4, 16, 0, 1, 3].iter().enumerate() {
+            let mut data = vec![1.0; *count];
+            smoother.process_at(&mut data, start + Duration::from_millis(16 * i as u64));
+            assert_eq!(smoother.values.len(), *count);
+            assert_eq!(data.len(), *count);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// audioviz starts the first bar here
+const MIN_FREQUENCY: f32 = 20.0;
+
+// one analyzed spectrum as it came out of audioviz
+#[derive(Debug, Clone)]
+pub struct SpectrumFrame {
+    pub bars: Vec<f32>,
+    pub ranges: Arc<Vec<(f32, f32)>>, // lowest and highest frequency of every bar in Hz
+    pub timestamp: Instant, // when the fetcher received it
+    pub generation: u64,    // counts up with every frame, gaps mean dropped frames
+}
+
+struct Snapshot {
+    data: Vec<f32>,
+    ranges: Arc<Vec<(f32, f32)>>,
+    timestamp: Instant,
+    generation: u64,
+}
+
+// frequencies covered by every bar, audioviz spreads the bars
+// logarithmically from 20 Hz up to its max frequency
+pub fn bar_ranges(bar_count: usize, max_frequency: f32) -> Vec<(f32, f32)> {
+    let edge = |i: usize| MIN_FREQUENCY * (max_frequency / MIN_FREQUENCY).powf(i as f32 / bar_count as f32);
+    (0..bar_count).map(|i| (edge(i), edge(i + 1))).collect()
+}
+
+// bar whose range contains `frequency`
+pub fn bar_for_frequency(ranges: &[(f32, f32)], frequency: f32) -> Option<usize> {
+    ranges.iter().position(|(low, high)| frequency >= *low && frequency < *high)
+}
+
+// read side of the spectrum buffer, never blocks the ui thread
+pub struct SpectrumReader {
+    shared: Arc<Mutex<Snapshot>>,
+    generation: u64,
+}
+
+impl SpectrumReader {
+    // returns the newest spectrum if one arrived since the last call,
+    // None if there is nothing new or the fetcher is writing right now
+    pub fn latest(&mut self) -> Option<SpectrumFrame> {
+        let snapshot = match self.shared.try_lock() {
+            Ok(s) => s,
+            Err(_) => return None,
+        };
+        if snapshot.generation == self.generation {
+            return None;
+        }
+        self.generation = snapshot.generation;
+
+        Some(SpectrumFrame {
+            bars: snapshot.data.clone(),
+            ranges: snapshot.ranges.clone(),
+            timestamp: snapshot.timestamp,
+            generation: snapshot.generation,
+        })
+    }
+}
+
+// requests data from audioviz on its own thread, so a stalled audioviz thread
+// only stalls this loop and not the ui
+pub fn init_spectrum_fetcher(
+    event_sender: mpsc::Sender<audioviz::Event>,
+    refresh_rate: Arc<AtomicUsize>,
+    max_frequency: Arc<AtomicUsize>,
+) -> SpectrumReader {
+    let shared = Arc::new(Mutex::new(Snapshot {
+        data: Vec::new(),
+        ranges: Arc::new(Vec::new()),
+        timestamp: Instant::now(),
+        generation: 0,
+    }));
+
+    let writer = shared.clone();
+    thread::spawn(move || {
+        let mut generation: u64 = 0;
+        let mut ranges = Arc::new(Vec::new());
+        let mut ranges_for = (0, 0); // bar count and max frequency of `ranges`
+        loop {
+            let (tx, rx) = mpsc::channel();
+            if event_sender.send(audioviz::Event::RequestData(tx)).is_err() {
+                break;
+            }
+            let mut data = match rx.recv() {
+                Ok(d) => d,
+                Err(_) => break,
+            };
+            generation += 1;
+
+            let max_frequency = max_frequency.load(Ordering::Relaxed);
+            if ranges_for != (data.len(), max_frequency) {
+                ranges = Arc::new(bar_ranges(data.len(), max_frequency as f32));
+                ranges_for = (data.len(), max_frequency);
+            }
+
+            // only swap under the lock to keep the critical section short
+            if let Ok(mut snapshot) = writer.lock() {
+                std::mem::swap(&mut snapshot.data, &mut data);
+                snapshot.ranges = ranges.clone();
+                snapshot.timestamp = Instant::now();
+                snapshot.generation = generation;
+            }
+
+            let rate = refresh_rate.load(Ordering::Relaxed).max(1);
+            thread::sleep(Duration::from_micros(1_000_000 / rate as u64));
+        }
+    });
+
+    SpectrumReader {
+        shared,
+        generation: 0,
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+// runtime statistics shown by the performance overlay
+pub struct Stats {
+    pub enabled: bool,
+    pub update_time: Duration,
+    pub late_frames: u64,
+    frames: u32,
+    fps: f32,
+    second_start: Instant,
+    last_frame: Instant,
+}
+
+impl Stats {
+    // call once per frame tick, `expected` is the configured frame interval
+    pub fn frame(&mut self, expected: Duration) {
+        let now = Instant::now();
+
+        // a frame counts as late if it arrives more than half an interval after it was due
+        if now.duration_since(self.last_frame) > expected + expected / 2 {
+            self.late_frames += 1;
+        }
+        self.last_frame = now;
+
+        self.frames += 1;
+        let elapsed = now.duration_since(self.second_start);
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.frames = 0;
+            self.second_start = now;
+        }
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            enabled: false,
+            update_time: Duration::ZERO,
+            late_frames: 0,
+            frames: 0,
+            fps: 0.0,
+            second_start: Instant::now(),
+            last_frame: Instant::now(),
+        }
+    }
+}
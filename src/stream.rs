@@ -0,0 +1,175 @@
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use colored::*;
+
+// NaN and infinite bars are sent as 0.0 in both formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    // one json object per line
+    Json,
+    // little endian: u64 timestamp (ms), u64 generation, u32 bar count, f32 per bar
+    Binary,
+}
+
+impl StreamFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamFormat::Json => "json",
+            StreamFormat::Binary => "binary",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(StreamFormat::Json),
+            "binary" => Some(StreamFormat::Binary),
+            _ => None,
+        }
+    }
+}
+
+// publishes spectra to every client connected to localhost:`port`
+pub struct StreamServer {
+    format: StreamFormat,
+    min_interval: Duration,
+    last_sent: Option<Instant>,
+    generation: u64,
+    sender: mpsc::SyncSender<Vec<u8>>,
+}
+
+impl StreamServer {
+    // `rate` caps the frames per second sent to clients
+    pub fn start(port: u16, rate: u32, format: StreamFormat) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted = clients.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // a stuck client must not hold back the others for long
+                let _ = stream.set_write_timeout(Some(Duration::from_millis(100)));
+                let _ = stream.set_nodelay(true);
+                if let Ok(mut clients) = accepted.lock() {
+                    clients.push(stream);
+                }
+            }
+        });
+
+        // frames are written on their own thread, if it falls behind new frames are dropped
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(1);
+        thread::spawn(move || {
+            for message in receiver {
+                if let Ok(mut clients) = clients.lock() {
+                    clients.retain(|mut c| c.write_all(&message).is_ok());
+                }
+            }
+        });
+
+        println!("streaming spectrum on {} as {}", format!("127.0.0.1:{}", port).green().bold(), format.name());
+
+        Ok(StreamServer {
+            format,
+            min_interval: Duration::from_secs_f32(1.0 / rate.max(1) as f32),
+            last_sent: None,
+            generation: 0,
+            sender,
+        })
+    }
+
+    pub fn publish(&mut self, bars: &[f32], config: &audioviz::Config) {
+        let now = Instant::now();
+        if let Some(last) = self.last_sent {
+            if now.duration_since(last) < self.min_interval {
+                return;
+            }
+        }
+        self.last_sent = Some(now);
+        self.generation += 1;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let message = encode(self.format, timestamp, self.generation, bars, config);
+
+        // full means the writer is still busy with the last frame
+        let _ = self.sender.try_send(message);
+    }
+}
+
+// one frame as it is written to the clients
+fn encode(format: StreamFormat, timestamp: u64, generation: u64, bars: &[f32], config: &audioviz::Config) -> Vec<u8> {
+    // json has no NaN or infinity and clients shouldn't have to deal with them either
+    let finite = |v: f32| if v.is_finite() { v } else { 0.0 };
+
+    match format {
+        StreamFormat::Json => {
+            let values: Vec<String> = bars.iter().map(|v| format!("{:.4}", finite(*v))).collect();
+            format!(
+                "{{\"timestamp\":{},\"generation\":{},\"bar_count\":{},\"max_frequency\":{},\"volume\":{},\"bars\":[{}]}}\n",
+                timestamp,
+                generation,
+                bars.len(),
+                config.max_frequency,
+                finite(config.volume),
+                values.join(","),
+            ).into_bytes()
+        }
+        StreamFormat::Binary => {
+            let mut message = Vec::with_capacity(20 + bars.len() * 4);
+            message.extend_from_slice(&timestamp.to_le_bytes());
+            message.extend_from_slice(&generation.to_le_bytes());
+            message.extend_from_slice(&(bars.len() as u32).to_le_bytes());
+            for v in bars {
+                message.extend_from_slice(&finite(*v).to_le_bytes());
+            }
+            message
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> audioviz::Config {
+        audioviz::Config {
+            max_frequency: 20_000,
+            volume: 0.5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn json() {
+        let message = encode(StreamFormat::Json, 1234, 7, &[0.5, 1.0 / 3.0, f32::NAN, f32::INFINITY], &config());
+        assert_eq!(
+            String::from_utf8(message).unwrap(),
+            "{\"timestamp\":1234,\"generation\":7,\"bar_count\":4,\"max_frequency\":20000,\"volume\":0.5,\"bars\":[0.5000,0.3333,0.0000,0.0000]}\n",
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let message = encode(StreamFormat::Binary, 1234, 7, &[0.5, f32::NEG_INFINITY], &config());
+        assert_eq!(message, [
+            1234u64.to_le_bytes().as_ref(),
+            &7u64.to_le_bytes(),
+            &2u32.to_le_bytes(),
+            &0.5f32.to_le_bytes(),
+            &0.0f32.to_le_bytes(),
+        ].concat());
+    }
+
+    #[test]
+    fn format_names() {
+        for format in [StreamFormat::Json, StreamFormat::Binary].iter() {
+            assert_eq!(StreamFormat::parse(format.name()), Some(*format));
+        }
+        assert_eq!(StreamFormat::parse("xml"), None);
+    }
+}
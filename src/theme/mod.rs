@@ -1,6 +1,6 @@
 use iced::{
     button, checkbox, container, progress_bar, radio, rule, scrollable,
-    slider, text_input, toggler,
+    slider, text_input, toggler, Color,
 };
 
 mod light;
@@ -16,6 +16,14 @@ pub enum Theme {
 
 impl Theme {
     pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::Midnight];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::Midnight => "midnight",
+        }
+    }
 }
 
 impl Default for Theme {
@@ -24,6 +32,19 @@ impl Default for Theme {
     }
 }
 
+// container without background, used by the transparent overlay mode
+pub struct TransparentContainer;
+
+impl container::StyleSheet for TransparentContainer {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: None,
+            text_color: Color::WHITE.into(),
+            ..container::Style::default()
+        }
+    }
+}
+
 impl From<Theme> for Box<dyn container::StyleSheet> {
     fn from(theme: Theme) -> Self {
         match theme {
@@ -1,18 +1,51 @@
 use iced::{
-    canvas::{self, Cache, Canvas, Cursor, Geometry},
+    canvas::{self, Cache, Canvas, Cursor, Geometry, Frame, Path},
     Color, Element, Length,
-    Point, Rectangle, Vector, Size,
+    Point, Rectangle, Size,
 };
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 use crate::Message;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    Linear,
+    Decibel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    Center,     // low frequencies in the middle
+    LeftRight,  // low frequencies at the left and right edge
+    TopBottom,  // bars grow up and down from the middle
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeatReaction {
+    Off,
+    Background, // the background lights up in the bar color on every beat
+    Flash,      // the bars turn white on every beat
+}
+
 pub struct Bars {
     pub data: Vec<f32>,
     pub cache: Cache,
     pub mirroring: bool,
+    pub mirror_axis: MirrorAxis,
     pub width: f32,
+    pub scaling: Scaling,
+    pub db_floor: f32,
+    pub db_ceiling: f32,
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    pub alpha: f32,
+    pub beat_reaction: BeatReaction,
+    pub pulse: f32, // 1.0 on a beat, decays to 0.0
+    pub highlight: Option<usize>, // index of a bar drawn in white, e.g. the one of the detected pitch
+    pub draw_time: Cell<Duration>, // set in draw(), which only gets &self
+    pub overlay: Vec<String>,      // lines of the performance and help overlay, hidden if empty
+    size: Cell<Size>,              // canvas size of the last draw, used for screenshots
 }
 
 impl Bars {
@@ -24,6 +57,109 @@ impl Bars {
             .height(Length::Fill)
             .into()
     }
+
+    // maps a raw value from audioviz to a fraction of the canvas height in 0.0..=1.0
+    pub fn normalize(&self, value: f32) -> f32 {
+        let value = match self.scaling {
+            Scaling::Linear => value,
+            Scaling::Decibel => {
+                let db = 20.0 * value.max(f32::MIN_POSITIVE).log10();
+                let range = (self.db_ceiling - self.db_floor).max(1.0);
+                (db - self.db_floor) / range
+            }
+        };
+        value.clamp(0.0, 1.0)
+    }
+
+    // builds every bar into one path so the whole spectrum is filled with a single call
+    pub fn draw_bars(&self, frame: &mut Frame) {
+        let size = frame.size();
+        let path = Path::new(|builder| {
+            self.for_each_bar(size, |_, point, size| builder.rectangle(point, size));
+        });
+
+        let mut color = Color::from_rgba8(self.r, self.g, self.b, self.alpha);
+        if self.beat_reaction == BeatReaction::Flash {
+            color.r += (1.0 - color.r) * self.pulse;
+            color.g += (1.0 - color.g) * self.pulse;
+            color.b += (1.0 - color.b) * self.pulse;
+        }
+
+        frame.fill(&path, color);
+
+        if let Some(highlight) = self.highlight {
+            let path = Path::new(|builder| {
+                self.for_each_bar(size, |i, point, size| {
+                    if i == highlight {
+                        builder.rectangle(point, size);
+                    }
+                });
+            });
+            frame.fill(&path, Color { a: self.alpha, ..Color::WHITE });
+        }
+    }
+
+    // renders the bars into an image on the cpu, used for screenshots
+    pub fn screenshot(&self) -> image::RgbaImage {
+        let size = self.size.get();
+        let (width, height) = (size.width as u32, size.height as u32);
+        let mut image = image::RgbaImage::new(width, height);
+        let color = image::Rgba([self.r, self.g, self.b, (self.alpha * 255.0) as u8]);
+
+        self.for_each_bar(size, |_, point, size| {
+            let x_end = ((point.x + size.width).ceil() as u32).min(width);
+            let y_end = ((point.y + size.height).ceil() as u32).min(height);
+            for y in point.y.max(0.0) as u32..y_end {
+                for x in point.x.max(0.0) as u32..x_end {
+                    image.put_pixel(x, y, color);
+                }
+            }
+        });
+
+        image
+    }
+
+    // calls `f` with the index in `data`, position and size of every visible bar
+    fn for_each_bar(&self, size: Size, mut f: impl FnMut(usize, Point, Size)) {
+        let count = self.data.len();
+
+        // mirroring only changes where each bar is drawn, the data itself is never copied
+        for (i, value) in self.data.iter().enumerate() {
+            match (self.mirroring, self.mirror_axis) {
+                (false, _) => {
+                    self.bar(&mut f, size, i, (i, count), *value, false);
+                }
+                (true, MirrorAxis::Center) => {
+                    self.bar(&mut f, size, i, (count - 1 - i, count * 2), *value, false);
+                    self.bar(&mut f, size, i, (count + i, count * 2), *value, false);
+                }
+                (true, MirrorAxis::LeftRight) => {
+                    self.bar(&mut f, size, i, (i, count * 2), *value, false);
+                    self.bar(&mut f, size, i, (count * 2 - 1 - i, count * 2), *value, false);
+                }
+                (true, MirrorAxis::TopBottom) => {
+                    self.bar(&mut f, size, i, (i, count), *value, true);
+                }
+            }
+        }
+    }
+
+    // one bar in `slot` of `slots` equally wide columns,
+    // `from_center` lets it grow from the vertical center instead of the bottom
+    fn bar(&self, f: &mut impl FnMut(usize, Point, Size), size: Size, index: usize, (slot, slots): (usize, usize), value: f32, from_center: bool) {
+        let size_y: f32 = self.normalize(value) * size.height;
+        if size_y <= 0.0 {
+            return;
+        }
+        let x: f32 = (size.width / slots as f32) * slot as f32;
+        let size_x: f32 = size.width / slots as f32 * self.width * 0.1;
+        let y: f32 = if from_center {
+            (size.height - size_y) * 0.5
+        } else {
+            size.height - size_y
+        };
+        f(index, Point::new(x, y), Size::new(size_x, size_y));
+    }
 }
 
 impl Default for Bars {
@@ -32,33 +168,64 @@ impl Default for Bars {
             data: Vec::new(),
             cache: Cache::new(),
             mirroring: true,
+            mirror_axis: MirrorAxis::Center,
             width: 10.0,
+            scaling: Scaling::Linear,
+            db_floor: -60.0,
+            db_ceiling: 0.0,
             r: 255,
             g: 0,
             b: 0,
+            alpha: 1.0,
+            beat_reaction: BeatReaction::Off,
+            pulse: 0.0,
+            highlight: None,
+            draw_time: Cell::new(Duration::ZERO),
+            overlay: Vec::new(),
+            size: Cell::new(Size::new(1920.0, 1080.0)),
         }
     }
 }
 
 impl canvas::Program<Message> for Bars {
     fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let start = Instant::now();
+        self.size.set(bounds.size());
         let clock = self.cache.draw(bounds.size(), |frame| {
-            let center = frame.center();
+            self.draw_bars(frame);
+        });
+        self.draw_time.set(start.elapsed());
 
-            for i in 0..self.data.len() {
-                let x: f32 = (frame.width() / self.data.len() as f32) * i as f32;
-                let mut y: f32 = frame.height() - self.data[i] * 100.0_f32;
-                if y < 0.0 {
-                    y = 1.0;
-                }
-                let size_x: f32 = frame.size().width / self.data.len() as f32 * self.width * 0.1;
-                let size_y: f32 = self.data[i] * 100.0_f32;
-                frame.fill_rectangle(Point::new(x, y), Size::new(size_x, size_y), Color::from_rgb8(self.r, self.g, self.b));
-            }
+        let mut geometry = Vec::new();
+        if self.beat_reaction == BeatReaction::Background && self.pulse > 0.0 {
+            // below the bars, changes every frame so it is not cached either
+            let mut frame = Frame::new(bounds.size());
+            frame.fill_rectangle(
+                Point::ORIGIN,
+                bounds.size(),
+                Color::from_rgba8(self.r, self.g, self.b, self.pulse * 0.3),
+            );
+            geometry.push(frame.into_geometry());
+        }
+        geometry.push(clock);
 
-            frame.translate(Vector::new(center.x, center.y));
-        });
+        if self.overlay.is_empty() {
+            return geometry;
+        }
+
+        // drawn every time and never cached, so the numbers stay current
+        let mut frame = Frame::new(bounds.size());
+        for (i, line) in self.overlay.iter().enumerate() {
+            frame.fill_text(canvas::Text {
+                content: line.clone(),
+                position: Point::new(5.0, 5.0 + i as f32 * 18.0),
+                color: Color::from_rgb8(self.r, self.g, self.b),
+                size: 16.0,
+                ..Default::default()
+            });
+        }
 
-        vec![clock]
+        geometry.push(frame.into_geometry());
+        geometry
     }
 }
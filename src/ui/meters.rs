@@ -0,0 +1,134 @@
+use iced::{
+    canvas::{self, Canvas, Cursor, Geometry, Frame},
+    button, alignment, Color, Column, Element, Length, Text,
+    Point, Rectangle, Size,
+};
+use audiolizer::meter::ChannelLevel;
+use audiolizer::loudness::Loudness;
+use crate::Message;
+use crate::theme::Theme;
+
+// lowest level the meters show
+const RANGE_DB: f32 = -60.0;
+const LABEL_HEIGHT: f32 = 20.0;
+
+// stereo vu / peak meter and three band meters, drawn next to the bars
+pub struct Meters {
+    pub channels: [ChannelLevel; 2],
+    pub bands: [f32; 3],
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Meters {
+    pub fn view<'a>(
+        &'a mut self,
+    ) -> Element<'a, Message> {
+        Canvas::new(self)
+            .width(Length::Units(160))
+            .height(Length::Fill)
+            .into()
+    }
+
+    // 0.0 at `RANGE_DB`, 1.0 at 0 dBFS
+    fn normalize(db: f32) -> f32 {
+        (1.0 - db / RANGE_DB).clamp(0.0, 1.0)
+    }
+
+    fn meter(&self, frame: &mut Frame, x: f32, width: f32, level: f32, peak: Option<(f32, f32)>, label: &str) {
+        let height = frame.size().height - LABEL_HEIGHT;
+        let color = Color::from_rgb8(self.r, self.g, self.b);
+
+        frame.fill_rectangle(Point::new(x, 0.0), Size::new(width, height), Color { a: 0.15, ..color });
+
+        let level_height = Meters::normalize(level) * height;
+        frame.fill_rectangle(Point::new(x, height - level_height), Size::new(width, level_height), color);
+
+        if let Some((peak, hold)) = peak {
+            let peak_y = height - Meters::normalize(peak) * height;
+            frame.fill_rectangle(Point::new(x, peak_y), Size::new(width, 2.0), Color { a: 0.6, ..Color::WHITE });
+            let hold_y = height - Meters::normalize(hold) * height;
+            frame.fill_rectangle(Point::new(x, hold_y), Size::new(width, 2.0), Color::WHITE);
+        }
+
+        frame.fill_text(canvas::Text {
+            content: label.to_string(),
+            position: Point::new(x, height + 2.0),
+            color,
+            size: 16.0,
+            ..Default::default()
+        });
+    }
+}
+
+impl Default for Meters {
+    fn default() -> Self {
+        Meters {
+            channels: [ChannelLevel::default(); 2],
+            bands: [RANGE_DB; 3],
+            r: 255,
+            g: 0,
+            b: 0,
+        }
+    }
+}
+
+impl canvas::Program<Message> for Meters {
+    // changes every frame, so nothing is cached
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+
+        // L R | B M T, with a wider gap between the two groups
+        let slot = bounds.width / 6.0;
+        let width = slot * 0.7;
+
+        for (i, (level, label)) in self.channels.iter().zip(["L", "R"].iter()).enumerate() {
+            self.meter(&mut frame, slot * i as f32, width, level.rms, Some((level.peak, level.hold)), label);
+        }
+        for (i, (level, label)) in self.bands.iter().zip(["B", "M", "T"].iter()).enumerate() {
+            self.meter(&mut frame, slot * (i + 3) as f32, width, *level, None, label);
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// readings of the R128 loudness meter with a reset for the integrated measurement
+pub struct LoudnessPanel {
+    reset_button: button::State,
+}
+
+impl LoudnessPanel {
+    pub fn new() -> Self {
+        LoudnessPanel {
+            reset_button: button::State::new(),
+        }
+    }
+
+    pub fn view<'a>(&'a mut self, loudness: &Loudness, theme: Theme) -> Element<'a, Message> {
+        let reading = |value: Option<f32>, unit: &str| match value {
+            Some(v) => format!("{:>6.1} {}", v, unit),
+            None => format!("    -- {}", unit),
+        };
+
+        let reset_button = button::Button::new(
+                &mut self.reset_button,
+                Text::new("reset").horizontal_alignment(alignment::Horizontal::Center),
+            )
+            .on_press(Message::ResetLoudness)
+            .style(theme);
+
+        Column::new()
+            .width(Length::Units(170))
+            .padding(5)
+            .spacing(5)
+            .push(Text::new("Loudness"))
+            .push(Text::new(format!("M  {}", reading(loudness.momentary(), "LUFS"))).size(16))
+            .push(Text::new(format!("S  {}", reading(loudness.short_term(), "LUFS"))).size(16))
+            .push(Text::new(format!("I  {}", reading(loudness.integrated(), "LUFS"))).size(16))
+            .push(Text::new(format!("TP {}", reading(loudness.true_peak(), "dBTP"))).size(16))
+            .push(reset_button)
+            .into()
+    }
+}
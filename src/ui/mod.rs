@@ -1,2 +1,4 @@
 pub mod bars;
-pub mod settings;
\ No newline at end of file
+pub mod meters;
+pub mod settings;
+pub mod slider_row;
\ No newline at end of file
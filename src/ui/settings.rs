@@ -1,11 +1,15 @@
 use iced::{
     Element, Row, Alignment, Text, Rule, Length, Column, Radio, scrollable, Checkbox,
-    slider, button, alignment,
+    button, alignment, Tooltip, tooltip,
 };
 use std::sync::mpsc;
 use audioviz;
 use crate::theme::Theme;
-use crate::audio::AudioDevice;
+use audiolizer::{Analyzer, AudioDevice};
+use crate::ui::bars::{Scaling, MirrorAxis, BeatReaction};
+use crate::config::{Config, SettingsLayout};
+use crate::keymap::Action;
+use crate::ui::slider_row::SliderRow;
 
 #[derive(Debug, Clone)]
 pub enum SettingMessage {
@@ -17,17 +21,210 @@ pub enum SettingMessage {
     SmoothingAmount(f32),
     ThemeChanged(Theme),
     Mirroring(bool),
+    MirrorAxisChanged(MirrorAxis),
     UpdateAudioDevices,
     AudioDeviceChanged(AudioDevice),
     BarWidthChanged(f32),
     BarRefreshRate(f32),
     MaxFreq(f32),
+    ScalingChanged(Scaling),
+    DbFloor(f32),
+    DbCeiling(f32),
+    AutoGain(bool),
+    AgcAttack(f32),
+    AgcRelease(f32),
+    BarAttack(f32),
+    BarRelease(f32),
+    FrameRate(f32),
+    Interpolation(bool),
+    PerformanceOverlay(bool),
+    BeatReactionChanged(BeatReaction),
+    ShowBpm(bool),
+    ShowPitch(bool),
+    Fullscreen(bool),
+    Borderless(bool),
+    AlwaysOnTop(bool),
+    HideSettingsButton(bool),
+    Transparent(bool),
+    ClickThrough(bool),
+    SettingsLayoutChanged(SettingsLayout),
+    BarOpacity(f32),
+    Meters(bool),
+    Loudness(bool),
+    CrossoverLow(f32),
+    CrossoverHigh(f32),
+    InputChanged(Field, String),
+    InputSubmitted(Field),
+    Reset(Field),
+    ResetAll,
+    TabSelected(Tab),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Audio,
+    Analysis,
+    Display,
+    Devices,
+    Appearance,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [Tab::Audio, Tab::Analysis, Tab::Display, Tab::Devices, Tab::Appearance];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Tab::Audio => "Audio",
+            Tab::Analysis => "Analysis",
+            Tab::Display => "Display",
+            Tab::Devices => "Devices",
+            Tab::Appearance => "Appearance",
+        }
+    }
+}
+
+// wraps `content` so `text` shows up when hovering it
+pub fn tooltip<'a>(content: impl Into<Element<'a, SettingMessage>>, text: &str, theme: Theme) -> Element<'a, SettingMessage> {
+    Tooltip::new(content, text, tooltip::Position::Top)
+        .gap(5)
+        .padding(5)
+        .size(16)
+        .style(theme)
+        .into()
+}
+
+// identifies a slider row, used by the text inputs next to each slider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Volume,
+    AgcAttack,
+    AgcRelease,
+    FftResolution,
+    BarCount,
+    Buffering,
+    SmoothingSize,
+    SmoothingAmount,
+    BarWidth,
+    BarAttack,
+    BarRelease,
+    BarRefreshRate,
+    FrameRate,
+    MaxFreq,
+    DbFloor,
+    DbCeiling,
+    BarOpacity,
+    CrossoverLow,
+    CrossoverHigh,
+}
+
+impl Field {
+    pub const ALL: [Field; 19] = [
+        Field::Volume,
+        Field::AgcAttack,
+        Field::AgcRelease,
+        Field::FftResolution,
+        Field::BarCount,
+        Field::Buffering,
+        Field::SmoothingSize,
+        Field::SmoothingAmount,
+        Field::BarWidth,
+        Field::BarAttack,
+        Field::BarRelease,
+        Field::BarRefreshRate,
+        Field::FrameRate,
+        Field::MaxFreq,
+        Field::DbFloor,
+        Field::DbCeiling,
+        Field::BarOpacity,
+        Field::CrossoverLow,
+        Field::CrossoverHigh,
+    ];
+
+    // name used by the remote control
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Volume => "volume",
+            Field::AgcAttack => "agc_attack",
+            Field::AgcRelease => "agc_release",
+            Field::FftResolution => "fft_resolution",
+            Field::BarCount => "bar_count",
+            Field::Buffering => "buffering",
+            Field::SmoothingSize => "smoothing_size",
+            Field::SmoothingAmount => "smoothing_amount",
+            Field::BarWidth => "bar_width",
+            Field::BarAttack => "bar_attack",
+            Field::BarRelease => "bar_release",
+            Field::BarRefreshRate => "bar_refresh_rate",
+            Field::FrameRate => "frame_rate",
+            Field::MaxFreq => "max_frequency",
+            Field::DbFloor => "db_floor",
+            Field::DbCeiling => "db_ceiling",
+            Field::BarOpacity => "bar_opacity",
+            Field::CrossoverLow => "crossover_low",
+            Field::CrossoverHigh => "crossover_high",
+        }
+    }
+
+    // the message the slider of this row sends
+    pub fn message(self, v: f32) -> SettingMessage {
+        match self {
+            Field::Volume => SettingMessage::Volume(v),
+            Field::AgcAttack => SettingMessage::AgcAttack(v),
+            Field::AgcRelease => SettingMessage::AgcRelease(v),
+            Field::FftResolution => SettingMessage::FftResolution(v),
+            Field::BarCount => SettingMessage::BarCount(v),
+            Field::Buffering => SettingMessage::Buffering(v),
+            Field::SmoothingSize => SettingMessage::SmoothingSize(v),
+            Field::SmoothingAmount => SettingMessage::SmoothingAmount(v),
+            Field::BarWidth => SettingMessage::BarWidthChanged(v),
+            Field::BarAttack => SettingMessage::BarAttack(v),
+            Field::BarRelease => SettingMessage::BarRelease(v),
+            Field::BarRefreshRate => SettingMessage::BarRefreshRate(v),
+            Field::FrameRate => SettingMessage::FrameRate(v),
+            Field::MaxFreq => SettingMessage::MaxFreq(v),
+            Field::DbFloor => SettingMessage::DbFloor(v),
+            Field::DbCeiling => SettingMessage::DbCeiling(v),
+            Field::BarOpacity => SettingMessage::BarOpacity(v),
+            Field::CrossoverLow => SettingMessage::CrossoverLow(v),
+            Field::CrossoverHigh => SettingMessage::CrossoverHigh(v),
+        }
+    }
+
+    // the row and value of a slider message, the opposite of `message`
+    pub fn of(message: &SettingMessage) -> Option<(Field, f32)> {
+        match *message {
+            SettingMessage::Volume(v) => Some((Field::Volume, v)),
+            SettingMessage::AgcAttack(v) => Some((Field::AgcAttack, v)),
+            SettingMessage::AgcRelease(v) => Some((Field::AgcRelease, v)),
+            SettingMessage::FftResolution(v) => Some((Field::FftResolution, v)),
+            SettingMessage::BarCount(v) => Some((Field::BarCount, v)),
+            SettingMessage::Buffering(v) => Some((Field::Buffering, v)),
+            SettingMessage::SmoothingSize(v) => Some((Field::SmoothingSize, v)),
+            SettingMessage::SmoothingAmount(v) => Some((Field::SmoothingAmount, v)),
+            SettingMessage::BarWidthChanged(v) => Some((Field::BarWidth, v)),
+            SettingMessage::BarAttack(v) => Some((Field::BarAttack, v)),
+            SettingMessage::BarRelease(v) => Some((Field::BarRelease, v)),
+            SettingMessage::BarRefreshRate(v) => Some((Field::BarRefreshRate, v)),
+            SettingMessage::FrameRate(v) => Some((Field::FrameRate, v)),
+            SettingMessage::MaxFreq(v) => Some((Field::MaxFreq, v)),
+            SettingMessage::DbFloor(v) => Some((Field::DbFloor, v)),
+            SettingMessage::DbCeiling(v) => Some((Field::DbCeiling, v)),
+            SettingMessage::BarOpacity(v) => Some((Field::BarOpacity, v)),
+            SettingMessage::CrossoverLow(v) => Some((Field::CrossoverLow, v)),
+            SettingMessage::CrossoverHigh(v) => Some((Field::CrossoverHigh, v)),
+            _ => None,
+        }
+    }
 }
 
 pub struct Settings {
     theme: Theme,
     event_sender: mpsc::Sender<audioviz::Event>,
+    config: audioviz::Config, // cached, so slider events need no round-trip to audioviz
     scrollable: scrollable::State,
+    reset_all_button: button::State,
+    tab: Tab,
+    tab_buttons: [button::State; 5],
 
 
     audio_device_sender: mpsc::Sender<AudioDevice>,
@@ -37,41 +234,67 @@ pub struct Settings {
     audio_device: AudioDevice,
 
     mirroring_checkbox: bool,
+    mirror_axis: MirrorAxis,
 
-    volume_s: slider::State,
-    volume_sv: f32,
+    volume: SliderRow,
 
-    bar_count_s: slider::State,
-    bar_count_sv: f32,
+    auto_gain_checkbox: bool,
+    pub agc_gain: f32, // needs to be public because the gain is computed in main.rs
 
-    fft_res_s: slider::State,
-    fft_res_sv: f32,
+    agc_attack: SliderRow,
+    agc_release: SliderRow,
+    bar_count: SliderRow,
+    fft_res: SliderRow,
+    smoothing_size: SliderRow,
+    smoothing_amount: SliderRow,
+    bar_width: SliderRow,
+    bar_attack: SliderRow,
+    bar_release: SliderRow,
+    bar_rr: SliderRow,
+    frame_rate: SliderRow,
 
-    smoothing_size_s: slider::State,
-    smoothing_size_sv: f32,
+    interpolation_checkbox: bool,
 
-    smoothing_amount_s: slider::State,
-    smoothing_amount_sv: f32,
+    performance_overlay_checkbox: bool,
+    beat_reaction: BeatReaction,
+    bpm_checkbox: bool,
+    pitch_checkbox: bool,
 
-    bar_width_s: slider::State,
-    bar_width_sv: f32,
+    fullscreen_checkbox: bool,
+    borderless_checkbox: bool,
+    always_on_top_checkbox: bool,
+    hide_settings_button_checkbox: bool,
+    transparent_checkbox: bool,
+    click_through_checkbox: bool,
+    settings_layout: SettingsLayout,
 
-    bar_rr_s: slider::State,
-    pub bar_rr_sv: f32, // needs to be public because of bar refresh subscription in main.rs
+    bar_opacity: SliderRow,
+    meters_checkbox: bool,
+    loudness_checkbox: bool,
+    crossover_low: SliderRow,
+    crossover_high: SliderRow,
+    max_freq: SliderRow,
 
-    max_freq_s: slider::State,
-    max_freq_sv: f32,
+    scaling: Scaling,
 
-    buffering_s: slider::State,
-    buffering_sv: f32,
+    db_floor: SliderRow,
+    db_ceiling: SliderRow,
+    buffering: SliderRow,
 }
 impl Settings {
-    pub fn new(event_sender: mpsc::Sender<audioviz::Event>, theme: Theme, config: audioviz::Config, audio_device_sender: mpsc::Sender<AudioDevice>) -> Self {
-        let (input_devices, output_devices) = crate::audio::iter_audio_devices();
+    pub fn new(event_sender: mpsc::Sender<audioviz::Event>, theme: Theme, config: audioviz::Config, audio_device_sender: mpsc::Sender<AudioDevice>, app_config: &Config) -> Self {
+        let (input_devices, output_devices) = Analyzer::devices();
+        // rows start at the given configs but reset to the defaults
+        let defaults = audioviz::Config::default();
+        let app_defaults = Config::default();
         Settings {
             theme,
             event_sender,
+            config: config.clone(),
             scrollable: scrollable::State::new(),
+            reset_all_button: button::State::new(),
+            tab: Tab::Audio,
+            tab_buttons: Default::default(),
 
             audio_device_sender,
             input_devices,
@@ -80,99 +303,126 @@ impl Settings {
             audio_device: AudioDevice::Output(0),
 
             mirroring_checkbox: true,
+            mirror_axis: MirrorAxis::Center,
 
-            volume_s: slider::State::new(),
-            volume_sv: config.volume.sqrt() * 100.0,
-            bar_count_s: slider::State::new(),
-            bar_count_sv: config.bar_count as f32,
-            fft_res_s: slider::State::new(),
-            fft_res_sv: config.fft_resolution as f32,
-            smoothing_size_s: slider::State::new(),
-            smoothing_size_sv: config.smoothing_size as f32,
-            smoothing_amount_s: slider::State::new(),
-            smoothing_amount_sv: config.smoothing_amount as f32,
-            buffering_s: slider::State::new(),
-            buffering_sv: config.buffering as f32,
-            bar_width_s: slider::State::new(),
-            bar_width_sv: 10.0,
-            bar_rr_s: slider::State::new(),
-            bar_rr_sv: 60.0,
-            max_freq_s: slider::State::new(),
-            max_freq_sv: config.max_frequency as f32 / 100.0,
+            volume: SliderRow::new("Volume", "Amplification of the signal before it is analyzed.", Field::Volume, 1.0..=150.0, 0, (defaults.volume * 100.0).sqrt())
+                .starting_at((config.volume * 100.0).sqrt()),
+            auto_gain_checkbox: false,
+            agc_gain: 1.0,
+            agc_attack: SliderRow::new("Attack (ms)", "How fast the automatic gain turns down when the input gets louder.", Field::AgcAttack, 1.0..=1000.0, 0, 50.0),
+            agc_release: SliderRow::new("Release (ms)", "How slowly the automatic gain turns back up after loud passages.", Field::AgcRelease, 10.0..=5000.0, 0, 2000.0),
+            bar_count: SliderRow::new("number of bars", "How many frequency bands the spectrum is split into.", Field::BarCount, 1.0..=2000.0, 0, defaults.bar_count as f32)
+                .starting_at(config.bar_count as f32),
+            fft_res: SliderRow::new("FFT resolution", "Samples per FFT. Higher values separate low frequencies better but react slower.", Field::FftResolution, 100.0..=16256.0, 0, defaults.fft_resolution as f32)
+                .starting_at(config.fft_resolution as f32),
+            smoothing_size: SliderRow::new("Smoothing Size", "How many neighbouring bars are blended into each other.", Field::SmoothingSize, 1.0..=20.0, 0, defaults.smoothing_size as f32)
+                .starting_at(config.smoothing_size as f32),
+            smoothing_amount: SliderRow::new("Smoothing Amount", "How often the neighbour blending is repeated, higher values give a rounder curve.", Field::SmoothingAmount, 1.0..=20.0, 0, defaults.smoothing_amount as f32)
+                .starting_at(config.smoothing_amount as f32),
+            buffering: SliderRow::new("Buffering", "Number of past spectra averaged together. Higher values give calmer but slower bars.", Field::Buffering, 1.0..=30.0, 0, defaults.buffering as f32)
+                .starting_at(config.buffering as f32),
+            bar_width: SliderRow::new("Bar Width", "Width of a bar relative to its slot, 10 leaves no gap between bars.", Field::BarWidth, 0.0..=10.0, 1, 10.0),
+            bar_attack: SliderRow::new("Bar attack (ms)", "Time a bar takes to rise to a new peak, 0 is instant.", Field::BarAttack, 0.0..=500.0, 0, 0.0),
+            bar_release: SliderRow::new("Bar release (ms)", "Time a bar takes to fall back down after a peak.", Field::BarRelease, 0.0..=3000.0, 0, 150.0),
+            bar_rr: SliderRow::new("Bar refresh rate", "How many spectra per second are requested from the analysis.", Field::BarRefreshRate, 1.0..=1000.0, 0, 60.0),
+            frame_rate: SliderRow::new("Frame rate", "How many times per second the bars are redrawn.", Field::FrameRate, 10.0..=240.0, 0, 60.0),
+            interpolation_checkbox: true,
+            performance_overlay_checkbox: false,
+            beat_reaction: BeatReaction::Off,
+            bpm_checkbox: false,
+            pitch_checkbox: false,
+            fullscreen_checkbox: app_config.fullscreen,
+            borderless_checkbox: app_config.borderless,
+            always_on_top_checkbox: app_config.always_on_top,
+            hide_settings_button_checkbox: app_config.hide_settings_button,
+            transparent_checkbox: app_config.transparent,
+            click_through_checkbox: app_config.click_through,
+            settings_layout: app_config.settings_layout,
+            bar_opacity: SliderRow::new("Bar opacity (%)", "Opacity of the bars, useful together with the transparent mode.", Field::BarOpacity, 0.0..=100.0, 0, 100.0),
+            meters_checkbox: false,
+            loudness_checkbox: false,
+            crossover_low: SliderRow::new("Bass / mid crossover (Hz)", "Frequency that separates the bass meter from the mid meter.", Field::CrossoverLow, 20.0..=1000.0, 0, app_defaults.crossover_low)
+                .starting_at(app_config.crossover_low),
+            crossover_high: SliderRow::new("Mid / treble crossover (Hz)", "Frequency that separates the mid meter from the treble meter.", Field::CrossoverHigh, 1000.0..=16000.0, 0, app_defaults.crossover_high)
+                .starting_at(app_config.crossover_high),
+            max_freq: SliderRow::new("Max frequency (x100 Hz)", "Highest frequency that is shown, in hundreds of Hz.", Field::MaxFreq, 10.0..=200.0, 0, defaults.max_frequency as f32 / 100.0)
+                .starting_at(config.max_frequency as f32 / 100.0),
+            scaling: Scaling::Linear,
+            db_floor: SliderRow::new("dB floor", "Level that is drawn as an empty bar in dB scaling.", Field::DbFloor, -120.0..=-10.0, 0, -60.0),
+            db_ceiling: SliderRow::new("dB ceiling", "Level that is drawn as a full height bar in dB scaling.", Field::DbCeiling, -40.0..=20.0, 0, 0.0),
         }
     }
 
     pub fn update(&mut self, msg: SettingMessage) {
-        let (tx, rx) = mpsc::channel();
-        self.event_sender.send(audioviz::Event::RequestConfig(tx)).unwrap();
-        let config = rx.recv().unwrap();
+        let config = self.config.clone();
 
         match msg {
             SettingMessage::Volume(v) => {
-                self.volume_sv = v;
+                self.volume.set(v);
+                let v = v.powi(2) * 0.01;
                 if config.volume != v {
                     let config = audioviz::Config {
-                        volume: v.powi(2) * 0.01,
+                        volume: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_config(config);
                 } 
             }
             SettingMessage::BarCount(v) => {
-                self.bar_count_sv = v;
+                self.bar_count.set(v);
                 let v = v as usize;
                 if config.bar_count != v {
                     let config = audioviz::Config {
                         bar_count: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::ClearBuffer).unwrap();
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_event(audioviz::Event::ClearBuffer);
+                    self.send_config(config);
                 }
             }
             SettingMessage::FftResolution(v) => {
-                self.fft_res_sv = v;
+                self.fft_res.set(v);
                 let v = v as usize;
                 if config.fft_resolution != v {
                     let config = audioviz::Config {
                         fft_resolution: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::ClearBuffer).unwrap();
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_event(audioviz::Event::ClearBuffer);
+                    self.send_config(config);
                 }
             }
             SettingMessage::Buffering(v) => {
-                self.buffering_sv = v;
+                self.buffering.set(v);
                 let v = v as usize;
                 if config.buffering != v {
                     let config = audioviz::Config {
                         buffering: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_config(config);
                 } 
             }
             SettingMessage::SmoothingSize(v) => {
-                self.smoothing_size_sv = v;
+                self.smoothing_size.set(v);
                 let v = v as usize;
-                if config.buffering != v {
+                if config.smoothing_size != v {
                     let config = audioviz::Config {
                         smoothing_size: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_config(config);
                 } 
             }
             SettingMessage::SmoothingAmount(v) => {
-                self.smoothing_amount_sv = v;
+                self.smoothing_amount.set(v);
                 let v = v as usize;
-                if config.buffering != v {
+                if config.smoothing_amount != v {
                     let config = audioviz::Config {
                         smoothing_amount: v,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_config(config);
                 } 
             }
             SettingMessage::ThemeChanged(t) => {
@@ -181,30 +431,93 @@ impl Settings {
             SettingMessage::Mirroring(b) => {
                 self.mirroring_checkbox = b;
             }
+            SettingMessage::MirrorAxisChanged(a) => {
+                self.mirror_axis = a;
+            }
             SettingMessage::UpdateAudioDevices => {
-                let (i, o) = crate::audio::iter_audio_devices();
+                let (i, o) = Analyzer::devices();
                 self.input_devices = i;
                 self.output_devices = o;
             }
             SettingMessage::AudioDeviceChanged(d) => {
                 self.audio_device = d;
-                self.audio_device_sender.send(d).unwrap();
+                let _ = self.audio_device_sender.send(d);
             }
             SettingMessage::BarWidthChanged(w) => {
-                self.bar_width_sv = w;
+                self.bar_width.set(w);
+            }
+            SettingMessage::BarAttack(a) => {
+                self.bar_attack.set(a);
+            }
+            SettingMessage::BarRelease(r) => {
+                self.bar_release.set(r);
             }
             SettingMessage::BarRefreshRate(r) => {
-                self.bar_rr_sv = r;
+                self.bar_rr.set(r);
                 if config.refresh_rate != r as usize {
                     let config = audioviz::Config {
                         refresh_rate: r as usize,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_config(config);
                 }
             }
+            SettingMessage::FrameRate(r) => {
+                self.frame_rate.set(r);
+            }
+            SettingMessage::Interpolation(b) => {
+                self.interpolation_checkbox = b;
+            }
+            SettingMessage::PerformanceOverlay(b) => {
+                self.performance_overlay_checkbox = b;
+            }
+            SettingMessage::BeatReactionChanged(r) => {
+                self.beat_reaction = r;
+            }
+            SettingMessage::ShowBpm(b) => {
+                self.bpm_checkbox = b;
+            }
+            SettingMessage::ShowPitch(b) => {
+                self.pitch_checkbox = b;
+            }
+            SettingMessage::Fullscreen(b) => {
+                self.fullscreen_checkbox = b;
+            }
+            SettingMessage::Borderless(b) => {
+                self.borderless_checkbox = b;
+            }
+            SettingMessage::AlwaysOnTop(b) => {
+                self.always_on_top_checkbox = b;
+            }
+            SettingMessage::HideSettingsButton(b) => {
+                self.hide_settings_button_checkbox = b;
+            }
+            SettingMessage::Transparent(b) => {
+                self.transparent_checkbox = b;
+            }
+            SettingMessage::ClickThrough(b) => {
+                self.click_through_checkbox = b;
+            }
+            SettingMessage::SettingsLayoutChanged(l) => {
+                self.settings_layout = l;
+            }
+            SettingMessage::BarOpacity(o) => {
+                self.bar_opacity.set(o);
+            }
+            SettingMessage::Meters(b) => {
+                self.meters_checkbox = b;
+            }
+            SettingMessage::Loudness(b) => {
+                self.loudness_checkbox = b;
+            }
+            SettingMessage::CrossoverLow(f) => {
+                self.crossover_low.set(f);
+            }
+            SettingMessage::CrossoverHigh(f) => {
+                self.crossover_high.set(f);
+            }
             SettingMessage::MaxFreq(m) => {
-                self.max_freq_sv = m;
+                self.max_freq.set(m);
                 let m = match m as usize {
                     1..=20_000 => m as usize * 100,
                     _ =>  20_000,
@@ -214,266 +527,610 @@ impl Settings {
                         max_frequency: m,
                         ..config
                     };
-                    self.event_sender.send(audioviz::Event::ClearBuffer).unwrap();
-                    self.event_sender.send(audioviz::Event::SendConfig(config)).unwrap();
+                    self.send_event(audioviz::Event::ClearBuffer);
+                    self.send_config(config);
                 } 
             }
+            SettingMessage::ScalingChanged(s) => {
+                self.scaling = s;
+            }
+            SettingMessage::DbFloor(f) => {
+                self.db_floor.set(f);
+            }
+            SettingMessage::DbCeiling(c) => {
+                self.db_ceiling.set(c);
+            }
+            SettingMessage::AutoGain(b) => {
+                self.auto_gain_checkbox = b;
+            }
+            SettingMessage::AgcAttack(a) => {
+                self.agc_attack.set(a);
+            }
+            SettingMessage::AgcRelease(r) => {
+                self.agc_release.set(r);
+            }
+            SettingMessage::InputChanged(field, text) => {
+                self.row(field).edit(text);
+            }
+            SettingMessage::InputSubmitted(_) => {}
+            SettingMessage::Reset(_) => {}
+            SettingMessage::TabSelected(t) => {
+                self.tab = t;
+            }
+            SettingMessage::ResetAll => {
+                // the rows were already reset through main.rs, this makes sure
+                // audioviz ends up with exactly its own defaults
+                self.send_event(audioviz::Event::ClearBuffer);
+                self.send_config(audioviz::Config::default());
+            }
         }
     }
 
-    pub fn view(&mut self) -> Element<SettingMessage> {
-        let mirroring_checkbox = Checkbox::new(
-                self.mirroring_checkbox,
-                String::from("mirroring"),
-                SettingMessage::Mirroring,
-            )
-            .style(self.theme);
-
-        let device_refresh_button = button::Button::new(
-                &mut self.device_refresh_button,
-                Text::new("refresh").horizontal_alignment(alignment::Horizontal::Center),
-            )
-            .on_press(SettingMessage::UpdateAudioDevices)
-            .style(self.theme);
-
-        let b_slider = slider::Slider::new(
-            &mut self.bar_count_s, 
-            1.0..=2000.0, 
-            self.bar_count_sv, 
-            SettingMessage::BarCount)
-            .style(self.theme);
-
-        let fft_r_slider = slider::Slider::new(
-            &mut self.fft_res_s, 
-            100.0..=16256.0, 
-            self.fft_res_sv, 
-            SettingMessage::FftResolution)
-            .style(self.theme);
-
-        let volume_slider = slider::Slider::new(
-            &mut self.volume_s, 
-            1.0..=150.0, 
-            self.volume_sv, 
-            SettingMessage::Volume)
-            .style(self.theme);
-        
-        let buffering_slider = slider::Slider::new(
-                &mut self.buffering_s, 
-                1.0..=30.0, 
-                self.buffering_sv, 
-                SettingMessage::Buffering
-            )
-            .style(self.theme);
-
-        let smoothing_size_slider = slider::Slider::new(
-                &mut self.smoothing_size_s,
-                1.0..=20.0, 
-                self.smoothing_size_sv, 
-                SettingMessage::SmoothingSize
-            )
-            .style(self.theme);
+    // the message that sets a row back to its default value
+    pub fn reset(&mut self, field: Field) -> SettingMessage {
+        field.message(self.row(field).default())
+    }
 
+    // messages restoring every slider and analysis/rendering toggle,
+    // theme, window options and the audio device are kept
+    pub fn reset_all(&mut self) -> Vec<SettingMessage> {
+        let mut messages: Vec<SettingMessage> = Field::ALL.iter()
+            .map(|f| self.reset(*f))
+            .collect();
+        messages.extend(vec![
+            SettingMessage::Mirroring(true),
+            SettingMessage::MirrorAxisChanged(MirrorAxis::Center),
+            SettingMessage::ScalingChanged(Scaling::Linear),
+            SettingMessage::AutoGain(false),
+            SettingMessage::Interpolation(true),
+        ]);
+        messages
+    }
 
-        let smoothing_amount_slider = slider::Slider::new(
-                &mut self.smoothing_amount_s,
-                1.0..=20.0, 
-                self.smoothing_amount_sv, 
-                SettingMessage::SmoothingAmount
-            )
-            .style(self.theme);
+    // messages that bring another `Settings` to the state of this one,
+    // used to fill the detached settings window
+    pub fn state(&mut self) -> Vec<SettingMessage> {
+        let mut messages: Vec<SettingMessage> = Field::ALL.iter()
+            .map(|f| f.message(self.value(*f)))
+            .collect();
+        messages.extend(vec![
+            SettingMessage::ThemeChanged(self.theme),
+            SettingMessage::Mirroring(self.mirroring_checkbox),
+            SettingMessage::MirrorAxisChanged(self.mirror_axis),
+            SettingMessage::ScalingChanged(self.scaling),
+            SettingMessage::AutoGain(self.auto_gain_checkbox),
+            SettingMessage::Interpolation(self.interpolation_checkbox),
+            SettingMessage::PerformanceOverlay(self.performance_overlay_checkbox),
+            SettingMessage::BeatReactionChanged(self.beat_reaction),
+            SettingMessage::ShowBpm(self.bpm_checkbox),
+            SettingMessage::ShowPitch(self.pitch_checkbox),
+            SettingMessage::Fullscreen(self.fullscreen_checkbox),
+            SettingMessage::Borderless(self.borderless_checkbox),
+            SettingMessage::AlwaysOnTop(self.always_on_top_checkbox),
+            SettingMessage::HideSettingsButton(self.hide_settings_button_checkbox),
+            SettingMessage::Transparent(self.transparent_checkbox),
+            SettingMessage::ClickThrough(self.click_through_checkbox),
+            SettingMessage::SettingsLayoutChanged(self.settings_layout),
+            SettingMessage::Meters(self.meters_checkbox),
+            SettingMessage::Loudness(self.loudness_checkbox),
+            SettingMessage::AudioDeviceChanged(self.audio_device),
+        ]);
+        messages
+    }
 
+    // parses the text input of a row, the returned message has to go through
+    // main.rs like a slider event would
+    pub fn submit(&mut self, field: Field) -> Option<SettingMessage> {
+        self.row(field).submit().map(|v| field.message(v))
+    }
 
+    pub fn bar_refresh_rate(&self) -> f32 {
+        self.bar_rr.value()
+    }
 
-        let volume = Row::new()
-            .padding(5)
-            .spacing(2)
-            .align_items(Alignment::End)
-            .push(Text::new("Volume").width(Length::FillPortion(1)))
-            .push(volume_slider.width(Length::FillPortion(5)));
+    pub fn frame_rate(&self) -> f32 {
+        self.frame_rate.value()
+    }
 
-        let bar_count = Row::new()
-            .padding(5)
-            .spacing(2)
-            .align_items(Alignment::End)
-            .push(Text::new("number of bars").width(Length::FillPortion(1)))
-            .push(b_slider.width(Length::FillPortion(5)));
+    pub fn value(&mut self, field: Field) -> f32 {
+        self.row(field).value()
+    }
 
-        let fft_bar_count = Row::new()
-            .padding(5)
-            .spacing(2)
-            .align_items(Alignment::End)
-            .push(Text::new("FFT resolution").width(Length::FillPortion(1)))
-            .push(fft_r_slider.width(Length::FillPortion(5)));
-        
-        let buffering = Row::new()
-            .padding(5)
-            .spacing(2)
-            .push(Text::new("Buffering").width(Length::FillPortion(1)))
-            .push(buffering_slider.width(Length::FillPortion(5)));
+    pub fn audio_device(&self) -> AudioDevice {
+        self.audio_device
+    }
 
-        let smoothing_size = Row::new()
-            .padding(5)
-            .spacing(2)
-            .push(Text::new("Smoothing Size").width(Length::FillPortion(1)))
-            .push(smoothing_size_slider.width(Length::FillPortion(5)));
+    // the config that was last sent to audioviz
+    pub fn audio_config(&self) -> &audioviz::Config {
+        &self.config
+    }
 
-        let smoothing_amount = Row::new()
-            .padding(5)
-            .spacing(2)
-            .push(Text::new("Smoothing Amount").width(Length::FillPortion(1)))
-            .push(smoothing_amount_slider.width(Length::FillPortion(5)));
+    fn row(&mut self, field: Field) -> &mut SliderRow {
+        match field {
+            Field::Volume => &mut self.volume,
+            Field::AgcAttack => &mut self.agc_attack,
+            Field::AgcRelease => &mut self.agc_release,
+            Field::FftResolution => &mut self.fft_res,
+            Field::BarCount => &mut self.bar_count,
+            Field::Buffering => &mut self.buffering,
+            Field::SmoothingSize => &mut self.smoothing_size,
+            Field::SmoothingAmount => &mut self.smoothing_amount,
+            Field::BarWidth => &mut self.bar_width,
+            Field::BarAttack => &mut self.bar_attack,
+            Field::BarRelease => &mut self.bar_release,
+            Field::BarRefreshRate => &mut self.bar_rr,
+            Field::FrameRate => &mut self.frame_rate,
+            Field::MaxFreq => &mut self.max_freq,
+            Field::DbFloor => &mut self.db_floor,
+            Field::DbCeiling => &mut self.db_ceiling,
+            Field::BarOpacity => &mut self.bar_opacity,
+            Field::CrossoverLow => &mut self.crossover_low,
+            Field::CrossoverHigh => &mut self.crossover_high,
+        }
+    }
 
+    // translates a keyboard shortcut into the messages the matching control would send
+    pub fn shortcut(&self, action: Action) -> Vec<SettingMessage> {
+        match action {
+            Action::CycleTheme => {
+                let i = Theme::ALL.iter().position(|t| *t == self.theme).unwrap_or(0);
+                vec![SettingMessage::ThemeChanged(Theme::ALL[(i + 1) % Theme::ALL.len()])]
+            }
+            Action::CycleMode => {
+                // off -> center -> left/right -> top/bottom -> off
+                match (self.mirroring_checkbox, self.mirror_axis) {
+                    (false, _) => vec![
+                        SettingMessage::Mirroring(true),
+                        SettingMessage::MirrorAxisChanged(MirrorAxis::Center),
+                    ],
+                    (true, MirrorAxis::Center) => vec![SettingMessage::MirrorAxisChanged(MirrorAxis::LeftRight)],
+                    (true, MirrorAxis::LeftRight) => vec![SettingMessage::MirrorAxisChanged(MirrorAxis::TopBottom)],
+                    (true, MirrorAxis::TopBottom) => vec![SettingMessage::Mirroring(false)],
+                }
+            }
+            Action::ToggleMirroring => vec![SettingMessage::Mirroring(!self.mirroring_checkbox)],
+            Action::VolumeUp => vec![SettingMessage::Volume((self.volume.value() + 5.0).min(150.0))],
+            Action::VolumeDown => vec![SettingMessage::Volume((self.volume.value() - 5.0).max(1.0))],
+            Action::BarCountUp => vec![SettingMessage::BarCount((self.bar_count.value() + 10.0).min(2000.0))],
+            Action::BarCountDown => vec![SettingMessage::BarCount((self.bar_count.value() - 10.0).max(1.0))],
+            Action::NextDevice => self.cycle_device(1),
+            Action::PreviousDevice => self.cycle_device(-1),
+            _ => Vec::new(),
+        }
+    }
 
-        //
-        // Theme selection
-        //
-        let light_radio = Radio::new(Theme::Light, "Light", Some(self.theme), SettingMessage::ThemeChanged)
-            .style(self.theme);
+    fn cycle_device(&self, step: isize) -> Vec<SettingMessage> {
+        let devices: Vec<AudioDevice> = (0..self.output_devices.len()).map(AudioDevice::Output)
+            .chain((0..self.input_devices.len()).map(AudioDevice::Input))
+            .collect();
+        if devices.is_empty() {
+            return Vec::new();
+        }
+        let i = devices.iter().position(|d| *d == self.audio_device).unwrap_or(0) as isize;
+        let next = (i + step).rem_euclid(devices.len() as isize) as usize;
 
-        let dark_radio = Radio::new(Theme::Dark, "Dark", Some(self.theme), SettingMessage::ThemeChanged)
-            .style(self.theme);
+        vec![SettingMessage::AudioDeviceChanged(devices[next])]
+    }
 
-        let midnight_radio = Radio::new(Theme::Midnight, "Midnight", Some(self.theme), SettingMessage::ThemeChanged)
-            .style(self.theme);
+    fn send_config(&mut self, config: audioviz::Config) {
+        self.config = config.clone();
+        self.send_event(audioviz::Event::SendConfig(config));
+    }
 
-        let theme_selection = Row::new()
-            .padding(2)
-            .spacing(25)
-            .push(light_radio)
-            .push(dark_radio)
-            .push(midnight_radio);
-        // END Theme Selection
+    // the audioviz thread is gone when the audio stream failed, the settings keep
+    // working and the spectrum fetcher stops, so there is nothing to report here
+    fn send_event(&self, event: audioviz::Event) {
+        let _ = self.event_sender.send(event);
+    }
 
+    pub fn view(&mut self) -> Element<SettingMessage> {
+        let theme = self.theme;
+        let current_tab = self.tab;
 
         //
-        // Audio device selection
+        // Tab selection
         //
-        let mut output_device_selection = Column::new()
+        let mut tabs = Row::new()
             .padding(5)
-            .spacing(5);
-        for (i, name) in self.output_devices.iter().enumerate() {
-            output_device_selection = output_device_selection.push(
-                Radio::new(AudioDevice::Output(i), name, Some(self.audio_device), SettingMessage::AudioDeviceChanged)
-                    .style(self.theme)
-            )
-        };
+            .spacing(5)
+            .align_items(Alignment::Center);
+        for (tab, state) in Tab::ALL.iter().zip(self.tab_buttons.iter_mut()) {
+            let mut tab_button = button::Button::new(
+                    state,
+                    Text::new(tab.name()).horizontal_alignment(alignment::Horizontal::Center),
+                )
+                .style(theme);
+            // the selected tab is shown as a disabled button
+            if *tab != current_tab {
+                tab_button = tab_button.on_press(SettingMessage::TabSelected(*tab));
+            }
+            tabs = tabs.push(tab_button);
+        }
 
-        let mut input_device_selection = Column::new()
-            .padding(5)
-            .spacing(5);
-        for (i, name) in self.input_devices.iter().enumerate() {
-            input_device_selection = input_device_selection.push(
-                Radio::new(AudioDevice::Input(i), name, Some(self.audio_device), SettingMessage::AudioDeviceChanged)
-                    .style(self.theme)
+        let reset_all_button = button::Button::new(
+                &mut self.reset_all_button,
+                Text::new("reset all").horizontal_alignment(alignment::Horizontal::Center),
             )
-        };
+            .on_press(SettingMessage::ResetAll)
+            .style(theme);
+        let tabs = tabs.push(tooltip(reset_all_button, "Restores the default of every slider and analysis option.", theme));
+        // END Tab selection
 
-        let device_selection = Row::new()
-            .padding(5)
-            .spacing(2)
-            .align_items(Alignment::Start)
-            .push(output_device_selection)
-            .push(input_device_selection);
+        let section: Element<SettingMessage> = match current_tab {
+            Tab::Audio => {
+                let auto_gain_checkbox = Checkbox::new(
+                        self.auto_gain_checkbox,
+                        String::from("auto gain"),
+                        SettingMessage::AutoGain,
+                    )
+                    .style(theme);
 
-        let device_selection = Column::new()
-            .padding(5)
-            .spacing(2)
-            .align_items(Alignment::Center)
-            .push(device_refresh_button)
-            .push(device_selection);
-        // END Audio device selection
+                Column::new()
+                    .push(self.volume.view(theme))
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .align_items(Alignment::Center)
+                            .push(tooltip(
+                                auto_gain_checkbox,
+                                "Adjusts the gain automatically so quiet and loud material fill the canvas alike.",
+                                theme,
+                            ))
+                            .push(Text::new(format!("gain: {:.2}x", self.agc_gain)))
+                    )
+                    .push(self.agc_attack.view(theme))
+                    .push(self.agc_release.view(theme))
+                    .into()
+            }
+            Tab::Analysis => {
+                Column::new()
+                    .push(self.fft_res.view(theme))
+                    .push(self.bar_count.view(theme))
+                    .push(self.buffering.view(theme))
+                    .push(self.smoothing_size.view(theme))
+                    .push(self.smoothing_amount.view(theme))
+                    .push(self.max_freq.view(theme))
+                    .push(self.bar_rr.view(theme))
+                    .into()
+            }
+            Tab::Display => {
+                let mirroring_checkbox = Checkbox::new(
+                        self.mirroring_checkbox,
+                        String::from("mirroring"),
+                        SettingMessage::Mirroring,
+                    )
+                    .style(theme);
 
+                let mirror_center_radio = Radio::new(MirrorAxis::Center, "center", Some(self.mirror_axis), SettingMessage::MirrorAxisChanged)
+                    .style(theme);
 
-        //
-        //  Bar Settings
-        //
-        let bar_width_slider = slider::Slider::new(
-                &mut self.bar_width_s,
-                0.0..=10.0,
-                self.bar_width_sv,
-                SettingMessage::BarWidthChanged,
-            )
-            .style(self.theme);
+                let mirror_left_right_radio = Radio::new(MirrorAxis::LeftRight, "left/right", Some(self.mirror_axis), SettingMessage::MirrorAxisChanged)
+                    .style(theme);
 
-        let bar_refresh_rate_slider = slider::Slider::new(
-                &mut self.bar_rr_s,
-                1.0..=1000.0,
-                self.bar_rr_sv,
-                SettingMessage::BarRefreshRate,
-            )
-            .style(self.theme);
+                let mirror_top_bottom_radio = Radio::new(MirrorAxis::TopBottom, "top/bottom", Some(self.mirror_axis), SettingMessage::MirrorAxisChanged)
+                    .style(theme);
 
-        let max_freq_slider = slider::Slider::new(
-                &mut self.max_freq_s,
-                10.0..=200.0,
-                self.max_freq_sv,
-                SettingMessage::MaxFreq,
-            )
-            .style(self.theme);
-        
-
-        let bar_settings = Column::new()
-            .push(
-            Row::new()
-                    .push(Text::new("Bar Width")
-                        .width(Length::FillPortion(1))
+                let interpolation_checkbox = Checkbox::new(
+                        self.interpolation_checkbox,
+                        String::from("interpolation"),
+                        SettingMessage::Interpolation,
                     )
-                    .push(bar_width_slider
-                        .width(Length::FillPortion(5))
+                    .style(theme);
+
+                let performance_overlay_checkbox = Checkbox::new(
+                        self.performance_overlay_checkbox,
+                        String::from("performance overlay"),
+                        SettingMessage::PerformanceOverlay,
                     )
-                )
+                    .style(theme);
+
+                let beat_off_radio = Radio::new(BeatReaction::Off, "off", Some(self.beat_reaction), SettingMessage::BeatReactionChanged)
+                    .style(theme);
+
+                let beat_background_radio = Radio::new(BeatReaction::Background, "pulse background", Some(self.beat_reaction), SettingMessage::BeatReactionChanged)
+                    .style(theme);
 
-            .push(
-            Row::new()
-                    .push(Text::new("Bar refresh rate")
-                        .width(Length::FillPortion(1))
+                let beat_flash_radio = Radio::new(BeatReaction::Flash, "flash bars", Some(self.beat_reaction), SettingMessage::BeatReactionChanged)
+                    .style(theme);
+
+                let meters_checkbox = Checkbox::new(
+                        self.meters_checkbox,
+                        String::from("level meters"),
+                        SettingMessage::Meters,
                     )
-                    .push(bar_refresh_rate_slider
-                        .width(Length::FillPortion(5))
+                    .style(theme);
+
+                let loudness_checkbox = Checkbox::new(
+                        self.loudness_checkbox,
+                        String::from("loudness (R128)"),
+                        SettingMessage::Loudness,
                     )
-                )
+                    .style(theme);
 
-            .push(
-                Row::new()
-                        .push(Text::new("Max frequency")
-                            .width(Length::FillPortion(1))
-                        )
-                        .push(max_freq_slider
-                            .width(Length::FillPortion(5))
-                        )
+                let pitch_checkbox = Checkbox::new(
+                        self.pitch_checkbox,
+                        String::from("pitch"),
+                        SettingMessage::ShowPitch,
                     )
-            .spacing(5)
-            .padding(5);
-        // END Bar Settings
+                    .style(theme);
+
+                let bpm_checkbox = Checkbox::new(
+                        self.bpm_checkbox,
+                        String::from("show bpm"),
+                        SettingMessage::ShowBpm,
+                    )
+                    .style(theme);
 
+                let linear_radio = Radio::new(Scaling::Linear, "Linear", Some(self.scaling), SettingMessage::ScalingChanged)
+                    .style(theme);
+
+                let decibel_radio = Radio::new(Scaling::Decibel, "dB", Some(self.scaling), SettingMessage::ScalingChanged)
+                    .style(theme);
+
+                Column::new()
+                    .push(self.bar_width.view(theme))
+                    .push(self.bar_attack.view(theme))
+                    .push(self.bar_release.view(theme))
+                    .push(self.frame_rate.view(theme))
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                interpolation_checkbox,
+                                "Blends between the last two spectra so bars move smoothly at any frame rate.",
+                                theme,
+                            ))
+                            .push(tooltip(
+                                performance_overlay_checkbox,
+                                "Shows fps, timings and the estimated latency on top of the bars.",
+                                theme,
+                            ))
+                    )
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                mirroring_checkbox,
+                                "Draws the spectrum twice, mirrored along the selected axis.",
+                                theme,
+                            ))
+                            .push(mirror_center_radio)
+                            .push(mirror_left_right_radio)
+                            .push(mirror_top_bottom_radio)
+                    )
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                Text::new("Scaling").width(Length::FillPortion(1)),
+                                "Linear shows raw amplitudes, dB maps the range between floor and ceiling logarithmically.",
+                                theme,
+                            ))
+                            .push(linear_radio)
+                            .push(decibel_radio)
+                    )
+                    .push(self.db_floor.view(theme))
+                    .push(self.db_ceiling.view(theme))
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                Text::new("Beats"),
+                                "Reaction to beats found by the onset detection on the lowest bars.",
+                                theme,
+                            ))
+                            .push(beat_off_radio)
+                            .push(beat_background_radio)
+                            .push(beat_flash_radio)
+                            .push(tooltip(bpm_checkbox, "Shows the estimated tempo in the top left corner.", theme))
+                    )
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                meters_checkbox,
+                                "Shows a stereo vu meter with peak hold and bass, mid and treble meters next to the bars.",
+                                theme,
+                            ))
+                            .push(tooltip(
+                                pitch_checkbox,
+                                "Shows the dominant frequency with note name and cents for tuning and highlights its bar.",
+                                theme,
+                            ))
+                            .push(tooltip(
+                                loudness_checkbox,
+                                "Shows momentary, short-term and integrated loudness and the true peak after EBU R128.",
+                                theme,
+                            ))
+                    )
+                    .push(self.crossover_low.view(theme))
+                    .push(self.crossover_high.view(theme))
+                    .into()
+            }
+            Tab::Devices => {
+                let device_refresh_button = button::Button::new(
+                        &mut self.device_refresh_button,
+                        Text::new("refresh").horizontal_alignment(alignment::Horizontal::Center),
+                    )
+                    .on_press(SettingMessage::UpdateAudioDevices)
+                    .style(theme);
+
+                let mut output_device_selection = Column::new()
+                    .padding(5)
+                    .spacing(5)
+                    .push(Text::new("Output"));
+                for (i, name) in self.output_devices.iter().enumerate() {
+                    output_device_selection = output_device_selection.push(
+                        Radio::new(AudioDevice::Output(i), name, Some(self.audio_device), SettingMessage::AudioDeviceChanged)
+                            .style(theme)
+                    )
+                };
+
+                let mut input_device_selection = Column::new()
+                    .padding(5)
+                    .spacing(5)
+                    .push(Text::new("Input"));
+                for (i, name) in self.input_devices.iter().enumerate() {
+                    input_device_selection = input_device_selection.push(
+                        Radio::new(AudioDevice::Input(i), name, Some(self.audio_device), SettingMessage::AudioDeviceChanged)
+                            .style(theme)
+                    )
+                };
+
+                let device_selection = Row::new()
+                    .padding(5)
+                    .spacing(2)
+                    .align_items(Alignment::Start)
+                    .push(output_device_selection)
+                    .push(input_device_selection);
+
+                Column::new()
+                    .padding(5)
+                    .spacing(2)
+                    .align_items(Alignment::Center)
+                    .push(tooltip(device_refresh_button, "Searches for newly connected audio devices.", theme))
+                    .push(device_selection)
+                    .into()
+            }
+            Tab::Appearance => {
+                let light_radio = Radio::new(Theme::Light, "Light", Some(theme), SettingMessage::ThemeChanged)
+                    .style(theme);
+
+                let dark_radio = Radio::new(Theme::Dark, "Dark", Some(theme), SettingMessage::ThemeChanged)
+                    .style(theme);
+
+                let midnight_radio = Radio::new(Theme::Midnight, "Midnight", Some(theme), SettingMessage::ThemeChanged)
+                    .style(theme);
+
+                let fullscreen_checkbox = Checkbox::new(
+                        self.fullscreen_checkbox,
+                        String::from("fullscreen (F11)"),
+                        SettingMessage::Fullscreen,
+                    )
+                    .style(theme);
+
+                let borderless_checkbox = Checkbox::new(
+                        self.borderless_checkbox,
+                        String::from("borderless (restart)"),
+                        SettingMessage::Borderless,
+                    )
+                    .style(theme);
+
+                let always_on_top_checkbox = Checkbox::new(
+                        self.always_on_top_checkbox,
+                        String::from("always on top (restart)"),
+                        SettingMessage::AlwaysOnTop,
+                    )
+                    .style(theme);
+
+                let hide_settings_button_checkbox = Checkbox::new(
+                        self.hide_settings_button_checkbox,
+                        String::from("hide settings button (F10)"),
+                        SettingMessage::HideSettingsButton,
+                    )
+                    .style(theme);
+
+                let transparent_checkbox = Checkbox::new(
+                        self.transparent_checkbox,
+                        String::from("transparent (restart)"),
+                        SettingMessage::Transparent,
+                    )
+                    .style(theme);
+
+                let click_through_checkbox = Checkbox::new(
+                        self.click_through_checkbox,
+                        String::from("click-through"),
+                        SettingMessage::ClickThrough,
+                    )
+                    .style(theme);
+
+                let layout_bottom_radio = Radio::new(SettingsLayout::Bottom, "bottom", Some(self.settings_layout), SettingMessage::SettingsLayoutChanged)
+                    .style(theme);
+
+                let layout_side_radio = Radio::new(SettingsLayout::Side, "side", Some(self.settings_layout), SettingMessage::SettingsLayoutChanged)
+                    .style(theme);
+
+                let layout_window_radio = Radio::new(SettingsLayout::Window, "window", Some(self.settings_layout), SettingMessage::SettingsLayoutChanged)
+                    .style(theme);
+
+                let window_checkboxes = vec![
+                    tooltip(fullscreen_checkbox, "Covers the whole screen.", theme),
+                    tooltip(borderless_checkbox, "Removes the title bar and window border.", theme),
+                    tooltip(always_on_top_checkbox, "Keeps the window above all other windows.", theme),
+                    tooltip(hide_settings_button_checkbox, "Hides the Settings button, F10 still opens this panel.", theme),
+                    tooltip(transparent_checkbox, "Only draws the bars, everything behind the window stays visible.", theme),
+                    tooltip(click_through_checkbox, "Clicks go to the windows behind. Alt+Tab and F10 or a settings window still reach this option.", theme),
+                ];
+
+                // the side panel is too narrow to fit all checkboxes in one row
+                let window_settings: Element<SettingMessage> = match self.settings_layout {
+                    SettingsLayout::Bottom => Row::with_children(window_checkboxes)
+                        .padding(5)
+                        .spacing(25)
+                        .into(),
+                    SettingsLayout::Side | SettingsLayout::Window => Column::with_children(window_checkboxes)
+                        .padding(5)
+                        .spacing(10)
+                        .into(),
+                };
+
+                Column::new()
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(light_radio)
+                            .push(dark_radio)
+                            .push(midnight_radio)
+                    )
+                    .push(Rule::horizontal(10))
+                    .push(window_settings)
+                    .push(
+                        Row::new()
+                            .padding(5)
+                            .spacing(25)
+                            .push(tooltip(
+                                Text::new("Settings panel"),
+                                "Bottom places this panel below the bars, side keeps the bars at full height next to it, window opens it in a window of its own.",
+                                theme,
+                            ))
+                            .push(layout_bottom_radio)
+                            .push(layout_side_radio)
+                            .push(layout_window_radio)
+                    )
+                    .push(self.bar_opacity.view(theme))
+                    .into()
+            }
+        };
 
-        
         let content = Column::new()
             .padding(2)
-            .align_items(Alignment::Center)
             .spacing(2)
-            .push(volume)
-            .push(fft_bar_count)
-            .push(bar_count)
-            .push(buffering)
-            .push(smoothing_size)
-            .push(smoothing_amount)
-            .push(mirroring_checkbox)
-            .push(Rule::horizontal(10))
-            .push(bar_settings)
+            .push(tabs)
             .push(Rule::horizontal(10))
-            .push(Rule::horizontal(10))
-            .push(device_selection)
-            .push(Rule::horizontal(10))
-            .push(theme_selection);
+            .push(section);
 
-        scrollable::Scrollable::new(&mut self.scrollable)
+        let scrollable = scrollable::Scrollable::new(&mut self.scrollable)
             .padding(5)
             .push(content)
             .width(Length::Fill)
-            .height(Length::Shrink)
-            .max_height(500)
-            .style(self.theme)
-            .into()
+            .style(theme);
+
+        // as a side panel or in their own window the settings may use the full height
+        match self.settings_layout {
+            SettingsLayout::Bottom => scrollable
+                .height(Length::Shrink)
+                .max_height(500)
+                .into(),
+            SettingsLayout::Side | SettingsLayout::Window => scrollable
+                .height(Length::Fill)
+                .into(),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,116 @@
+use iced::{
+    Element, Row, Alignment, Text, Length, TextInput,
+    slider, text_input, button, alignment,
+};
+use std::ops::RangeInclusive;
+use crate::theme::Theme;
+use crate::ui::settings::{self, Field, SettingMessage};
+
+// a labeled slider with a text field showing its value, which also accepts typed input
+pub struct SliderRow {
+    label: &'static str,
+    tooltip: &'static str,
+    field: Field,
+    range: RangeInclusive<f32>,
+    decimals: usize,
+    default: f32,
+    value: f32,
+    text: String,
+    slider: slider::State,
+    input: text_input::State,
+    reset_button: button::State,
+}
+
+impl SliderRow {
+    pub fn new(label: &'static str, tooltip: &'static str, field: Field, range: RangeInclusive<f32>, decimals: usize, value: f32) -> Self {
+        SliderRow {
+            label,
+            tooltip,
+            field,
+            range,
+            decimals,
+            default: value,
+            value,
+            text: format!("{:.*}", decimals, value),
+            slider: slider::State::new(),
+            input: text_input::State::new(),
+            reset_button: button::State::new(),
+        }
+    }
+
+    // shows `value` while a reset still returns to the value given to `new`
+    pub fn starting_at(mut self, value: f32) -> Self {
+        self.set(value);
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    // the value a reset returns to
+    pub fn default(&self) -> f32 {
+        self.default
+    }
+
+    pub fn set(&mut self, value: f32) {
+        self.value = value;
+        self.text = format!("{:.*}", self.decimals, value);
+    }
+
+    pub fn edit(&mut self, text: String) {
+        self.text = text;
+    }
+
+    // parses the typed value and clamps it into the slider range,
+    // invalid input restores the current value
+    pub fn submit(&mut self) -> Option<f32> {
+        match self.text.trim().parse::<f32>() {
+            Ok(v) if v.is_finite() => Some(v.clamp(*self.range.start(), *self.range.end())),
+            _ => {
+                self.set(self.value);
+                None
+            }
+        }
+    }
+
+    pub fn view(&mut self, theme: Theme) -> Element<SettingMessage> {
+        let field = self.field;
+
+        let slider = slider::Slider::new(
+                &mut self.slider,
+                self.range.clone(),
+                self.value,
+                move |v| field.message(v),
+            )
+            .style(theme);
+
+        let input = TextInput::new(
+                &mut self.input,
+                "",
+                &self.text,
+                move |t| SettingMessage::InputChanged(field, t),
+            )
+            .on_submit(SettingMessage::InputSubmitted(field))
+            .padding(2)
+            .width(Length::Units(70))
+            .style(theme);
+
+        let reset = button::Button::new(
+                &mut self.reset_button,
+                Text::new("reset").horizontal_alignment(alignment::Horizontal::Center),
+            )
+            .on_press(SettingMessage::Reset(field))
+            .style(theme);
+
+        Row::new()
+            .padding(5)
+            .spacing(5)
+            .align_items(Alignment::Center)
+            .push(settings::tooltip(Text::new(self.label).width(Length::FillPortion(1)), self.tooltip, theme))
+            .push(slider.width(Length::FillPortion(5)))
+            .push(input)
+            .push(reset)
+            .into()
+    }
+}